@@ -0,0 +1,50 @@
+//!
+//! Timings for lexing a large program, with and without the ASCII fast path
+//!
+//! Run with `cargo bench --bench lex`. The fast path only applies to input
+//! that's entirely ASCII, so the same program with a trailing `-- λ` comment
+//! stands in for the char-based path; both lex to the same tokens
+//!
+//! On one machine, a program of about 1.6MB lexed in about 12.5ms through chars
+//! and 9ms through bytes
+//!
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use simple::lexer::Lexer;
+
+/// Lex `input` `iterations` times, returning the median duration and the number
+/// of tokens
+fn measure(input: &str, iterations: usize) -> (Duration, usize) {
+  let mut tokens = 0;
+  let mut samples: Vec<_> = (0..iterations)
+    .map(|_| {
+      let start = Instant::now();
+      tokens = Lexer::new(black_box(input)).count();
+      start.elapsed()
+    })
+    .collect();
+  samples.sort();
+  (samples[samples.len() / 2], tokens)
+}
+
+fn main() {
+  let program: String = (0..20_000)
+    .map(|n| {
+      format!("def{n} = \\f, x -> f (f{n} x) -- definition {n}\n  (let y = {n} in y def{n});\n")
+    })
+    .collect();
+  let fallback = format!("{program}-- λ\n");
+  println!(
+    "{:<12} {:>10} {:>12} {:>10}",
+    "path", "bytes", "median", "tokens"
+  );
+  for (name, input) in [("chars", &fallback), ("ascii", &program)] {
+    let (median, tokens) = measure(input, 30);
+    println!(
+      "{name:<12} {:>10} {median:>12.3?} {tokens:>10}",
+      input.len()
+    );
+  }
+}
@@ -0,0 +1,89 @@
+//!
+//! One-shot helpers that run the lexer, parser, and interpreter in a single call,
+//! for when the individual stages don't need configuring
+//!
+
+use std::error::Error as StdError;
+use std::fmt;
+
+use crate::interpreter::{EvalError, Interpreter};
+use crate::lexer::Lexer;
+use crate::parser::{ParseError, Parser};
+use crate::term::Term;
+
+/// Anything that can go wrong between source text and a normal form
+#[derive(Debug, PartialEq)]
+pub enum Error<'src> {
+  Parse(ParseError<'src>),
+  Eval(EvalError),
+}
+
+impl<'src> fmt::Display for Error<'src> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Error::Parse(e) => write!(f, "parse error: {}", e),
+      Error::Eval(e) => write!(f, "evaluation error: {}", e),
+    }
+  }
+}
+
+impl<'src> StdError for Error<'src> {
+  fn source(&self) -> Option<&(dyn StdError + 'static)> {
+    match self {
+      // parse errors borrow from the input, so can't be handed out as 'static
+      Error::Parse(_) => None,
+      Error::Eval(e) => Some(e),
+    }
+  }
+}
+
+impl<'src> From<ParseError<'src>> for Error<'src> {
+  fn from(e: ParseError<'src>) -> Self {
+    Error::Parse(e)
+  }
+}
+
+impl<'src> From<EvalError> for Error<'src> {
+  fn from(e: EvalError) -> Self {
+    Error::Eval(e)
+  }
+}
+
+/// Lex and parse a single term
+pub fn parse_str(input: &str) -> Result<Term<'_>, ParseError<'_>> {
+  Parser::new(Lexer::new(input)).parse()
+}
+
+/// Lex, parse, and evaluate a single term with a default `Interpreter`
+pub fn eval_str(input: &str) -> Result<Term<'_>, Error<'_>> {
+  let term = parse_str(input)?;
+  Ok(Interpreter::new().try_evaluate(&term)?)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  mod api {
+    use super::*;
+
+    #[test]
+    fn parse_str_term() {
+      assert_eq!(parse_str("λx. x"), Ok(Term::abs("x", Term::var("x"))));
+    }
+
+    #[test]
+    fn eval_str_reduces() {
+      assert_eq!(eval_str("(λx. x) y"), Ok(Term::Variable("y".into())));
+    }
+
+    #[test]
+    fn eval_str_syntax_error() {
+      assert_eq!(
+        eval_str("(λx. x"),
+        Err(Error::Parse(ParseError::UnclosedParen { opened_at: 0 }))
+      );
+      assert!(eval_str("λ. x").is_err());
+    }
+  }
+}
@@ -0,0 +1,297 @@
+//!
+//! Terms stored contiguously in one buffer, referring to their subterms by index
+//! rather than through a pointer each, for workloads building many transient terms
+//!
+//! Nodes are never freed individually, an arena only grows until it's `clear`ed,
+//! so it suits building and normalizing some terms, reading off the results, then
+//! starting over
+//!
+
+use std::collections::HashSet;
+
+use crate::symbol::{Symbol, SymbolTable};
+use crate::term::Term;
+
+/// Handle to a node stored in a `TermArena`
+/// - only meaningful alongside the arena that produced it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TermId(u32);
+
+/// A single node of an arena-backed term, its subterms given by id
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Node {
+  Variable(Symbol),
+  Abstraction { param: Symbol, body: TermId },
+  Application { lhs: TermId, rhs: TermId },
+}
+
+/// Owns every node of the terms built in it, along with their names
+#[derive(Debug, Default, Clone)]
+pub struct TermArena {
+  nodes: Vec<Node>,
+  symbols: SymbolTable,
+}
+
+impl TermArena {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Make room for `capacity` nodes up front, so building that many takes no
+  /// further allocation
+  pub fn with_capacity(capacity: usize) -> Self {
+    Self {
+      nodes: Vec::with_capacity(capacity),
+      symbols: SymbolTable::new(),
+    }
+  }
+
+  /// Store a node, giving the id to refer to it by
+  pub fn alloc(&mut self, node: Node) -> TermId {
+    let id = TermId(self.nodes.len() as u32);
+    self.nodes.push(node);
+    id
+  }
+
+  /// The node an id refers to
+  pub fn get(&self, id: TermId) -> Node {
+    self.nodes[id.0 as usize]
+  }
+
+  pub fn var(&mut self, name: &str) -> TermId {
+    let name = self.symbols.intern(name);
+    self.alloc(Node::Variable(name))
+  }
+
+  pub fn abs(&mut self, param: &str, body: TermId) -> TermId {
+    let param = self.symbols.intern(param);
+    self.alloc(Node::Abstraction { param, body })
+  }
+
+  pub fn app(&mut self, lhs: TermId, rhs: TermId) -> TermId {
+    self.alloc(Node::Application { lhs, rhs })
+  }
+
+  /// Number of nodes stored, including any no longer reachable from a term in use
+  pub fn len(&self) -> usize {
+    self.nodes.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.nodes.is_empty()
+  }
+
+  /// The names of the arena's variables
+  pub fn symbols(&self) -> &SymbolTable {
+    &self.symbols
+  }
+
+  /// Drop every node, invalidating all ids, but keep the buffer to build into
+  /// again
+  pub fn clear(&mut self) {
+    self.nodes.clear();
+  }
+
+  /// Copy a term into the arena
+  pub fn insert(&mut self, term: &Term<'_>) -> TermId {
+    match term {
+      Term::Variable(name) => self.var(name),
+      Term::Abstraction { param, body } => {
+        let body = self.insert(body);
+        self.abs(param, body)
+      }
+      Term::Application { lhs, rhs } => {
+        let lhs = self.insert(lhs);
+        let rhs = self.insert(rhs);
+        self.app(lhs, rhs)
+      }
+    }
+  }
+
+  /// Copy a term back out of the arena
+  pub fn to_term(&self, id: TermId) -> Term<'static> {
+    match self.get(id) {
+      Node::Variable(name) => Term::var(self.symbols.resolve(name).to_string()),
+      Node::Abstraction { param, body } => {
+        Term::abs(self.symbols.resolve(param).to_string(), self.to_term(body))
+      }
+      Node::Application { lhs, rhs } => Term::app(self.to_term(lhs), self.to_term(rhs)),
+    }
+  }
+
+  /// Normalize a term in place, by normal-order β-reduction like
+  /// `Interpreter::normalize`, giving the normal form, or `None` if there was
+  /// none within `max_steps`
+  /// - binders are renamed with primes to avoid capture, like `fresh::FreshNamer`
+  pub fn normalize(&mut self, mut id: TermId, max_steps: usize) -> Option<TermId> {
+    for _ in 0..max_steps {
+      match self.step(id) {
+        Some(next) => id = next,
+        None => return Some(id),
+      }
+    }
+    // the cap may have been hit just as the normal form was reached
+    self.step(id).is_none().then_some(id)
+  }
+
+  /// Contract the leftmost outermost redex, if there is one
+  fn step(&mut self, id: TermId) -> Option<TermId> {
+    match self.get(id) {
+      Node::Variable(_) => None,
+      Node::Abstraction { param, body } => {
+        let body = self.step(body)?;
+        Some(self.alloc(Node::Abstraction { param, body }))
+      }
+      Node::Application { lhs, rhs } => {
+        if let Node::Abstraction { param, body } = self.get(lhs) {
+          return Some(self.substitute(body, param, rhs));
+        }
+        if let Some(lhs) = self.step(lhs) {
+          return Some(self.app(lhs, rhs));
+        }
+        let rhs = self.step(rhs)?;
+        Some(self.app(lhs, rhs))
+      }
+    }
+  }
+
+  /// `id` with free occurrences of `var` replaced by `value`, α-converting
+  /// binders that would otherwise capture one of its free variables
+  /// - subterms `var` isn't free in are shared rather than copied
+  fn substitute(&mut self, id: TermId, var: Symbol, value: TermId) -> TermId {
+    if !self.occurs_free(id, var) {
+      return id;
+    }
+    match self.get(id) {
+      Node::Variable(_) => value,
+      Node::Abstraction { param, body } => {
+        if !self.occurs_free(value, param) {
+          let body = self.substitute(body, var, value);
+          return self.alloc(Node::Abstraction { param, body });
+        }
+        // rename the binder to something neither side can see
+        let mut used = self.free_variables(body);
+        used.extend(self.free_variables(value));
+        used.insert(var);
+        let fresh = self.symbols.fresh(param, &used);
+        let fresh_var = self.alloc(Node::Variable(fresh));
+        let renamed = self.substitute(body, param, fresh_var);
+        let body = self.substitute(renamed, var, value);
+        self.alloc(Node::Abstraction { param: fresh, body })
+      }
+      Node::Application { lhs, rhs } => {
+        let lhs = self.substitute(lhs, var, value);
+        let rhs = self.substitute(rhs, var, value);
+        self.app(lhs, rhs)
+      }
+    }
+  }
+
+  fn occurs_free(&self, id: TermId, var: Symbol) -> bool {
+    match self.get(id) {
+      Node::Variable(name) => name == var,
+      Node::Abstraction { param, body } => param != var && self.occurs_free(body, var),
+      Node::Application { lhs, rhs } => self.occurs_free(lhs, var) || self.occurs_free(rhs, var),
+    }
+  }
+
+  fn free_variables(&self, id: TermId) -> HashSet<Symbol> {
+    match self.get(id) {
+      Node::Variable(name) => HashSet::from([name]),
+      Node::Abstraction { param, body } => {
+        let mut free = self.free_variables(body);
+        free.remove(&param);
+        free
+      }
+      Node::Application { lhs, rhs } => {
+        let mut free = self.free_variables(lhs);
+        free.extend(self.free_variables(rhs));
+        free
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  mod term_arena {
+    use super::*;
+
+    use crate::api::parse_str;
+    use crate::interpreter::Interpreter;
+    use crate::prelude;
+
+    fn omega() -> Term<'static> {
+      parse_str("(λx. x x) (λx. x x)").unwrap().to_owned_term()
+    }
+
+    #[test]
+    fn insert_round_trips() {
+      let mut arena = TermArena::new();
+      for input in ["x", "λx. x y", "(λf. λx. f (f x)) (λy. y) z"] {
+        let term = parse_str(input).unwrap();
+        let id = arena.insert(&term);
+        assert_eq!(arena.to_term(id), term);
+      }
+      assert_eq!(arena.len(), 1 + 4 + 12);
+    }
+
+    #[test]
+    fn built_by_hand() {
+      let mut arena = TermArena::new();
+      let x = arena.var("x");
+      let body = arena.app(x, x);
+      let id = arena.abs("x", body);
+      assert_eq!(arena.to_term(id), parse_str("λx. x x").unwrap());
+      // every occurrence of a name shares one symbol
+      assert_eq!(arena.symbols().len(), 1);
+    }
+
+    #[test]
+    fn normalize_matches_interpreter() {
+      let terms = [
+        parse_str("(λx. x) y").unwrap(),
+        parse_str("λf. (λx. f x) (λz. z)").unwrap(),
+        // has to rename the binder to avoid capturing `y`
+        parse_str("(λx. λy. x y) y").unwrap(),
+        Term::apps([prelude::plus(), prelude::numeral(2), prelude::numeral(3)]).unwrap(),
+        Term::apps([prelude::mult(), prelude::numeral(3), prelude::numeral(4)]).unwrap(),
+        // normal order skips the diverging argument
+        Term::apps([prelude::k(), prelude::i(), omega()]).unwrap(),
+      ];
+      for term in terms {
+        let mut arena = TermArena::new();
+        let id = arena.insert(&term);
+        let normal = arena.normalize(id, 1000).unwrap();
+        let expected = Interpreter::new().normalize(&term, 1000);
+        assert!(expected.normal_form);
+        assert_eq!(arena.to_term(normal), expected.term, "{term}");
+      }
+    }
+
+    #[test]
+    fn normalize_gives_up() {
+      let mut arena = TermArena::new();
+      let id = arena.insert(&omega());
+      assert_eq!(arena.normalize(id, 50), None);
+      // exactly enough steps is enough
+      let id = arena.insert(&parse_str("(λx. x) ((λx. x) y)").unwrap());
+      let normal = arena.normalize(id, 2).unwrap();
+      assert_eq!(arena.to_term(normal), parse_str("y").unwrap());
+    }
+
+    #[test]
+    fn clear_reuses_buffer() {
+      let mut arena = TermArena::new();
+      arena.insert(&prelude::numeral(20));
+      assert!(!arena.is_empty());
+      arena.clear();
+      assert!(arena.is_empty());
+      let id = arena.insert(&prelude::numeral(3));
+      assert_eq!(arena.to_term(id).as_church_numeral(), Some(3));
+      assert_eq!(arena.len(), 9);
+    }
+  }
+}
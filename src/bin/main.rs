@@ -9,10 +9,10 @@ fn main() {
   match parser.parse() {
     Ok(root) => {
       use simple::term::Evaluate;
-      let mut interp = Interpreter;
+      let mut interp = Interpreter::new();
       println!("original: {}", &root);
       println!("simplified: {}", interp.evaluate(&root));
     }
-    Err(e) => println!("{e:?}"),
+    Err(e) => println!("{}", e.render(input)),
   }
 }
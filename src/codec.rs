@@ -0,0 +1,298 @@
+//!
+//! Compact binary encoding of terms, e.g. for caching parsed programs
+//!
+//! Each node is written in pre-order as a tag byte, `0` for a variable, `1` for
+//! an abstraction and `2` for an application. Variables and abstractions follow
+//! theirs with a name, written as its length in bytes (an LEB128 varint) then
+//! its UTF-8 bytes
+//!
+
+use std::error::Error;
+use std::fmt;
+
+use crate::term::{OwnedTerm, Term};
+
+const VARIABLE: u8 = 0;
+const ABSTRACTION: u8 = 1;
+const APPLICATION: u8 = 2;
+
+/// Why some bytes couldn't be decoded as a term
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+  /// The input ended partway through a term
+  UnexpectedEnd,
+  /// A node started with a byte that isn't any variant's tag
+  InvalidTag(u8),
+  /// A name's length didn't fit in a `usize`
+  LengthOverflow,
+  /// A name's bytes weren't valid UTF-8
+  InvalidName,
+  /// There were bytes left over after a complete term
+  TrailingBytes,
+  /// The term nested deeper than allowed, see `Term::from_bytes_with_max_depth`
+  TooDeep,
+}
+
+impl fmt::Display for DecodeError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      DecodeError::UnexpectedEnd => write!(f, "unexpected end of input"),
+      DecodeError::InvalidTag(tag) => write!(f, "invalid tag {}", tag),
+      DecodeError::LengthOverflow => write!(f, "name length overflows"),
+      DecodeError::InvalidName => write!(f, "name is not valid UTF-8"),
+      DecodeError::TrailingBytes => write!(f, "trailing bytes after term"),
+      DecodeError::TooDeep => write!(f, "term nested too deeply"),
+    }
+  }
+}
+
+impl Error for DecodeError {}
+
+impl<'src> Term<'src> {
+  /// Encode the term, see the module docs for the format
+  /// - encodes without recursing, so however deeply the term nests, it can't
+  ///   exhaust the stack
+  pub fn to_bytes(&self) -> Vec<u8> {
+    let mut out = Vec::new();
+    // subterms still to write, the next one on top
+    let mut todo = vec![self];
+    while let Some(term) = todo.pop() {
+      match term {
+        Term::Variable(name) => {
+          out.push(VARIABLE);
+          write_name(name, &mut out);
+        }
+        Term::Abstraction { param, body } => {
+          out.push(ABSTRACTION);
+          write_name(param, &mut out);
+          todo.push(body);
+        }
+        Term::Application { lhs, rhs } => {
+          out.push(APPLICATION);
+          todo.push(rhs);
+          todo.push(lhs);
+        }
+      }
+    }
+    out
+  }
+
+  /// Decode a term written by `to_bytes`, failing rather than panicking on
+  /// anything else
+  /// - decodes without recursing, but the term is still dropped (and displayed,
+  ///   evaluated, ...) by recursion, so for untrusted input see
+  ///   `from_bytes_with_max_depth`
+  pub fn from_bytes(bytes: &[u8]) -> Result<OwnedTerm, DecodeError> {
+    decode(bytes, None)
+  }
+
+  /// Like `from_bytes`, but fail with `DecodeError::TooDeep` rather than decode a
+  /// term nesting abstractions and applications deeper than `max_depth`, much
+  /// like `Parser::with_max_nesting`
+  pub fn from_bytes_with_max_depth(
+    bytes: &[u8],
+    max_depth: usize,
+  ) -> Result<OwnedTerm, DecodeError> {
+    decode(bytes, Some(max_depth))
+  }
+}
+
+/// Decode a whole term, with at most `max_depth` abstractions and applications
+/// open at once if given
+fn decode(bytes: &[u8], max_depth: Option<usize>) -> Result<OwnedTerm, DecodeError> {
+  /// What a decoded subterm is about to become part of
+  enum Pending {
+    Body(String),
+    Lhs,
+    Rhs(OwnedTerm),
+  }
+  let mut reader = Reader { bytes };
+  let mut pending = Vec::new();
+  loop {
+    let tag = reader.byte()?;
+    if tag != VARIABLE && max_depth.is_some_and(|max| pending.len() >= max) {
+      return Err(DecodeError::TooDeep);
+    }
+    let mut term = match tag {
+      VARIABLE => Term::var(reader.name()?),
+      ABSTRACTION => {
+        pending.push(Pending::Body(reader.name()?));
+        continue;
+      }
+      APPLICATION => {
+        pending.push(Pending::Lhs);
+        continue;
+      }
+      tag => return Err(DecodeError::InvalidTag(tag)),
+    };
+    // fold the finished subterm into whatever it completes, until something
+    // is still waiting on more input
+    loop {
+      match pending.pop() {
+        Some(Pending::Body(param)) => term = Term::abs(param, term),
+        Some(Pending::Lhs) => {
+          pending.push(Pending::Rhs(term));
+          break;
+        }
+        Some(Pending::Rhs(lhs)) => term = Term::app(lhs, term),
+        None if reader.bytes.is_empty() => return Ok(term),
+        None => return Err(DecodeError::TrailingBytes),
+      }
+    }
+  }
+}
+
+fn write_name(name: &str, out: &mut Vec<u8>) {
+  let mut len = name.len();
+  loop {
+    let byte = (len & 0x7f) as u8;
+    len >>= 7;
+    if len == 0 {
+      out.push(byte);
+      break;
+    }
+    out.push(byte | 0x80);
+  }
+  out.extend_from_slice(name.as_bytes());
+}
+
+/// Consumes the input from the front
+struct Reader<'a> {
+  bytes: &'a [u8],
+}
+
+impl Reader<'_> {
+  fn byte(&mut self) -> Result<u8, DecodeError> {
+    let (&byte, rest) = self.bytes.split_first().ok_or(DecodeError::UnexpectedEnd)?;
+    self.bytes = rest;
+    Ok(byte)
+  }
+
+  fn len(&mut self) -> Result<usize, DecodeError> {
+    let mut len = 0usize;
+    let mut shift = 0;
+    loop {
+      let byte = self.byte()?;
+      let bits = usize::from(byte & 0x7f);
+      if shift >= usize::BITS || (bits << shift) >> shift != bits {
+        return Err(DecodeError::LengthOverflow);
+      }
+      len |= bits << shift;
+      if byte & 0x80 == 0 {
+        return Ok(len);
+      }
+      shift += 7;
+    }
+  }
+
+  fn name(&mut self) -> Result<String, DecodeError> {
+    let len = self.len()?;
+    if len > self.bytes.len() {
+      return Err(DecodeError::UnexpectedEnd);
+    }
+    let (name, rest) = self.bytes.split_at(len);
+    self.bytes = rest;
+    String::from_utf8(name.to_vec()).map_err(|_| DecodeError::InvalidName)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  mod codec {
+    use super::*;
+
+    use crate::api::parse_str;
+    use crate::prelude;
+
+    #[test]
+    fn to_bytes_layout() {
+      let term = parse_str("λx. x y").unwrap();
+      assert_eq!(term.to_bytes(), [1, 1, b'x', 2, 0, 1, b'x', 0, 1, b'y']);
+      // lengths are in bytes, and past 127 take more than one
+      assert_eq!(Term::var("λ").to_bytes(), [0, 2, 0xce, 0xbb]);
+      let long = "a".repeat(300);
+      assert_eq!(Term::var(long.as_str()).to_bytes()[..3], [0, 0xac, 0x02]);
+    }
+
+    #[test]
+    fn round_trips() {
+      let long = "a".repeat(300);
+      for term in [
+        parse_str("x").unwrap(),
+        parse_str("λf. λx. f (f x)").unwrap(),
+        parse_str("(λx. x x) (λx. x x)").unwrap(),
+        parse_str("λx₁. `let` x₁").unwrap(),
+        Term::abs("x'", Term::var(long.as_str())),
+        prelude::y(),
+        Term::apps([prelude::mult(), prelude::numeral(10), prelude::numeral(3)]).unwrap(),
+      ] {
+        assert_eq!(Term::from_bytes(&term.to_bytes()), Ok(term.to_owned_term()));
+      }
+    }
+
+    #[test]
+    fn from_bytes_malformed() {
+      assert_eq!(Term::from_bytes(&[]), Err(DecodeError::UnexpectedEnd));
+      assert_eq!(Term::from_bytes(&[3]), Err(DecodeError::InvalidTag(3)));
+      // application missing its argument
+      assert_eq!(
+        Term::from_bytes(&[2, 0, 1, b'f']),
+        Err(DecodeError::UnexpectedEnd)
+      );
+      // name longer than what's left
+      assert_eq!(
+        Term::from_bytes(&[0, 5, b'x']),
+        Err(DecodeError::UnexpectedEnd)
+      );
+      assert_eq!(
+        Term::from_bytes(&[0, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x7f]),
+        Err(DecodeError::LengthOverflow)
+      );
+      assert_eq!(
+        Term::from_bytes(&[0, 1, 0xff]),
+        Err(DecodeError::InvalidName)
+      );
+      assert_eq!(
+        Term::from_bytes(&[0, 1, b'x', 0]),
+        Err(DecodeError::TrailingBytes)
+      );
+    }
+
+    #[test]
+    fn from_bytes_max_depth() {
+      // a million abstractions deep, the sort of input a limit is for
+      let mut bytes = [ABSTRACTION, 1, b'x'].repeat(1_000_000);
+      bytes.extend([VARIABLE, 1, b'x']);
+      assert_eq!(
+        Term::from_bytes_with_max_depth(&bytes, 1_000),
+        Err(DecodeError::TooDeep)
+      );
+      // the limit itself is fine
+      let term = prelude::numeral(20);
+      let bytes = term.to_bytes();
+      assert_eq!(
+        Term::from_bytes_with_max_depth(&bytes, 22),
+        Ok(term.to_owned_term())
+      );
+      assert_eq!(
+        Term::from_bytes_with_max_depth(&bytes, 21),
+        Err(DecodeError::TooDeep)
+      );
+      assert_eq!(
+        Term::from_bytes_with_max_depth(&[VARIABLE, 1, b'x'], 0),
+        Ok(Term::var("x"))
+      );
+    }
+
+    #[test]
+    fn from_bytes_deep_nesting() {
+      // a million nested applications would overflow the stack if decoded by recursion
+      let mut bytes = vec![APPLICATION; 1_000_000];
+      assert_eq!(Term::from_bytes(&bytes), Err(DecodeError::UnexpectedEnd));
+      bytes.resize(bytes.len() + 2, VARIABLE);
+      assert_eq!(Term::from_bytes(&bytes), Err(DecodeError::UnexpectedEnd));
+    }
+  }
+}
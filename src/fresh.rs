@@ -0,0 +1,72 @@
+//!
+//! Generation of variable names guaranteed not to clash with existing ones
+//!
+
+use std::collections::HashSet;
+use std::fmt;
+
+/// A policy for minting names during α-conversion
+///
+/// Implementors must return a name that isn't in `used`, though they're free to
+/// choose how (primes, numeric suffixes, some prefix, ...)
+pub trait NameGen {
+  fn fresh(&mut self, base: &str, used: &HashSet<&str>) -> String;
+}
+
+impl Default for Box<dyn NameGen> {
+  fn default() -> Self {
+    Box::new(FreshNamer::new())
+  }
+}
+
+impl fmt::Debug for dyn NameGen {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "NameGen")
+  }
+}
+
+/// Default `NameGen`, which mints names by priming a base name until it is unused
+#[derive(Debug, Default, Clone, Copy)]
+pub struct FreshNamer;
+
+impl FreshNamer {
+  pub fn new() -> Self {
+    Self
+  }
+}
+
+impl NameGen for FreshNamer {
+  /// Produce the first of `base`, `base'`, `base''`, ... that isn't in `used`
+  fn fresh(&mut self, base: &str, used: &HashSet<&str>) -> String {
+    let mut name = base.to_string();
+    while used.contains(name.as_str()) {
+      name.push('\'');
+    }
+    name
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  mod fresh_namer {
+    use super::*;
+
+    #[test]
+    fn fresh_unused_base() {
+      let mut namer = FreshNamer::new();
+      let used = HashSet::from(["y"]);
+      assert_eq!(namer.fresh("x", &used), "x");
+    }
+
+    #[test]
+    fn fresh_avoids_used() {
+      let mut namer = FreshNamer::new();
+      let used = HashSet::from(["x", "x'"]);
+      let name = namer.fresh("x", &used);
+      assert!(!used.contains(name.as_str()));
+      assert_eq!(name, "x''");
+    }
+  }
+}
@@ -2,59 +2,1103 @@
 //! Provide some concrete way to simplify/evaluate a root `Term<'src>` node
 //!
 
-use crate::term::{Evaluate, Term};
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::rc::Rc;
+use std::time::{Duration, Instant};
 
-/// Exists only as an implementor of `term::Evaluate`
-pub struct Interpreter;
+use crate::fresh::NameGen;
+use crate::prelude;
+use crate::term::{Evaluate, Step, Term};
+
+/// Counters collected over the course of a single evaluation
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct EvalStats {
+  /// Number of redexes contracted
+  pub beta_reductions: usize,
+  /// Number of variable occurrences replaced during substitution
+  pub substitutions: usize,
+  /// Deepest level of nesting reached while evaluating
+  pub max_depth: usize,
+}
+
+/// Where step-limited normalization ended up, see `Interpreter::normalize`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EvalOutcome<'src> {
+  /// The term reached, simplified as far as the step cap allowed
+  pub term: Term<'src>,
+  /// Whether no redex remained, rather than evaluation being cut short
+  pub normal_form: bool,
+  /// Whether the term has no free variables, so a normal form is a fully
+  /// computed value rather than stuck on unknowns (see `Term::is_closed`)
+  pub closed: bool,
+  /// Number of β-reductions performed
+  pub steps: usize,
+}
+
+/// A single contraction made by `Interpreter::reductions`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReductionStep<'src> {
+  /// The whole term, before the redex was contracted
+  pub before: Term<'src>,
+  /// Steps from the root of `before` down to the redex, see `Term::at`
+  pub redex_path: Vec<Step>,
+  /// The whole term, after the redex was contracted
+  pub after: Term<'src>,
+}
+
+/// Iterator over the contractions normalizing a term, see `Interpreter::reductions`
+/// - ends once the term reaches normal form, so never ends for one without
+pub struct Reductions<'a, 'src> {
+  interpreter: &'a mut Interpreter,
+  term: Option<Term<'src>>,
+}
+
+impl<'src> Iterator for Reductions<'_, 'src> {
+  type Item = ReductionStep<'src>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    let before = self.term.take()?;
+    let mut redex_path = Vec::new();
+    let after = self.interpreter.step_at(&before, &mut redex_path)?;
+    self.term = Some(after.clone());
+    Some(ReductionStep {
+      before,
+      redex_path,
+      after,
+    })
+  }
+}
+
+/// Evaluation can fail, but only once some limit has been configured
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EvalError {
+  /// More β-reductions were needed than the step limit allows
+  StepLimitExceeded,
+  /// A term needed its own value (up to α) to be computed, so it would never finish
+  Diverges,
+  /// Evaluation nested deeper than the recursion limit allows
+  RecursionLimit,
+  /// A β-reduction produced a term with more nodes than the size limit allows
+  TermTooLarge,
+  /// Evaluation took longer than the timeout allows
+  Timeout,
+}
+
+impl fmt::Display for EvalError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      EvalError::StepLimitExceeded => write!(f, "step limit exceeded"),
+      EvalError::Diverges => write!(f, "evaluation diverges"),
+      EvalError::RecursionLimit => write!(f, "recursion limit exceeded"),
+      EvalError::TermTooLarge => write!(f, "term size limit exceeded"),
+      EvalError::Timeout => write!(f, "evaluation timed out"),
+    }
+  }
+}
+
+impl Error for EvalError {}
+
+/// Which side of an application to evaluate first
+/// - for terms that terminate either way the result is the same, only the order
+///   redexes are contracted in differs
+/// - either way, an application stuck on something other than an abstraction,
+///   like `f ((λy. y) z)`, is kept with both sides evaluated, giving `f z`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum EvalOrder {
+  /// The function, then its argument
+  #[default]
+  FunctionFirst,
+  /// The argument, then the function it's passed to
+  ArgumentFirst,
+}
+
+/// Something that happened during evaluation, as reported to an observer (see
+/// `Interpreter::with_observer`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReductionEvent<'a> {
+  /// A redex was contracted
+  Beta {
+    redex: &'a Term<'a>,
+    contractum: &'a Term<'a>,
+  },
+  /// Free occurrences of `var` in `term` were replaced by `value`
+  Substitution {
+    term: &'a Term<'a>,
+    var: &'a str,
+    value: &'a Term<'a>,
+    result: &'a Term<'a>,
+  },
+}
+
+/// Callback fired for every `ReductionEvent`
+pub type Observer = Box<dyn FnMut(&ReductionEvent<'_>)>;
+
+/// Number of β-reductions between checks of the clock, see `Interpreter::with_timeout`
+pub const TIMEOUT_CHECK_INTERVAL: usize = 1024;
+
+/// Step budget of `Interpreter::normal_form_hash` when `with_max_steps` isn't set
+pub const DEFAULT_HASH_STEPS: usize = 10_000;
+
+/// One pass of an evaluation loop, either finished or with where to carry on
+/// from in place of a tail call, so evaluating a divergent term like Ω loops
+/// rather than using up the stack
+enum Tail<T, K> {
+  Done(T),
+  Continue(K),
+}
+
+/// Main implementor of `term::Evaluate`
+#[derive(Debug, Default)]
+pub struct Interpreter {
+  namer: Box<dyn NameGen>,
+  stats: EvalStats,
+  depth: usize,
+  max_steps: Option<usize>,
+  max_depth: Option<usize>,
+  max_term_size: Option<usize>,
+  timeout: Option<Duration>,
+  /// When the current evaluation started, only kept with a timeout
+  started: Option<Instant>,
+  cycle_window: Option<usize>,
+  /// Contracta currently being evaluated, innermost last
+  /// - entries older than the cycle window are dropped to bound memory
+  pending: Vec<Option<Term<'static>>>,
+  /// Whether to keep reducing inside abstraction bodies
+  full: bool,
+  /// Normal forms of previously evaluated terms, keyed by their canonical form
+  cache: Option<HashMap<Term<'static>, Term<'static>>>,
+  /// Definitions for free variables, only set during `evaluate_in_env`
+  env: HashMap<String, Term<'static>>,
+  /// Binders currently being reduced under, which shadow `env`
+  scope: Vec<String>,
+  order: EvalOrder,
+  /// Redexes contracted during the last evaluation, if asked to keep them
+  trace: Option<Vec<Term<'static>>>,
+  observer: Option<ObserverSlot>,
+  /// Whether to undo unneeded α-conversion once evaluation finishes
+  restore_names: bool,
+  /// Whether to evaluate call-by-need rather than by substitution
+  lazy: bool,
+  /// Binder names whose abstractions are never applied
+  opaque: HashSet<String>,
+  /// Names in `env` defined as arithmetic on numerals, if computing that
+  /// directly was asked for
+  arithmetic: Option<HashMap<String, Arithmetic>>,
+}
+
+/// An arithmetic combinator from the prelude, recognized in `env` up to α
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Arithmetic {
+  Plus,
+  Mult,
+}
+
+impl Arithmetic {
+  fn recognize(definition: &Term<'_>) -> Option<Self> {
+    if definition.alpha_eq(&prelude::plus()) {
+      Some(Arithmetic::Plus)
+    } else if definition.alpha_eq(&prelude::mult()) {
+      Some(Arithmetic::Mult)
+    } else {
+      None
+    }
+  }
+
+  fn apply(self, m: u64, n: u64) -> Option<u64> {
+    match self {
+      Arithmetic::Plus => m.checked_add(n),
+      Arithmetic::Mult => m.checked_mul(n),
+    }
+  }
+}
+
+/// Holds the observer, so `Interpreter` can still be `Debug`
+struct ObserverSlot(Observer);
+
+impl fmt::Debug for ObserverSlot {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "Observer")
+  }
+}
 
 impl<'src> Interpreter {
-  /// Recursively simplify a given term to evaluate it
-  fn evaluate_term(&mut self, term: &Term<'src>) -> Term<'src> {
-    match term {
-      // Evaluate applications
-      Term::Application { lhs, rhs } => {
-        let lhs_eval = self.evaluate_term(lhs);
-        let rhs_eval = self.evaluate_term(rhs);
-        // Apply the abstraction if the left-hand side is one
-        if let Term::Abstraction { param, body } = lhs_eval {
-          let subs = self.substitute(&body, param, &rhs_eval);
-          self.evaluate_term(&subs)
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Reduce inside abstraction bodies too, giving the full β-normal form rather
+  /// than stopping at the outermost abstraction
+  pub fn with_full_normalization(mut self) -> Self {
+    self.full = true;
+    self
+  }
+
+  /// Give up with `EvalError::StepLimitExceeded` after this many β-reductions
+  /// - a contractum in tail position is evaluated in place, but evaluation still
+  ///   nests deeper with each step of some runaway recursions, like one through
+  ///   `prelude::y`, so a generous limit wants `with_recursion_limit` too
+  pub fn with_max_steps(mut self, max_steps: usize) -> Self {
+    self.max_steps = Some(max_steps);
+    self
+  }
+
+  /// Give up with `EvalError::RecursionLimit` rather than nesting evaluation
+  /// deeper than this, since running out of native stack aborts the process
+  pub fn with_recursion_limit(mut self, max_depth: usize) -> Self {
+    self.max_depth = Some(max_depth);
+    self
+  }
+
+  /// Give up with `EvalError::TermTooLarge` once a β-reduction produces a term of
+  /// more than this many nodes (see `Term::size`), bounding memory use
+  /// - under call-by-need, only the final result is checked
+  pub fn with_max_term_size(mut self, max_term_size: usize) -> Self {
+    self.max_term_size = Some(max_term_size);
+    self
+  }
+
+  /// Give up with `EvalError::Timeout` once evaluation has taken longer than
+  /// `timeout`, going by wall-clock time
+  /// - the clock is only read every `TIMEOUT_CHECK_INTERVAL` β-reductions, so
+  ///   evaluation may overrun a little
+  /// - a term like Ω, always contracting in tail position, runs in constant stack
+  ///   until then, but one nesting deeper each step wants `with_recursion_limit` too
+  pub fn with_timeout(mut self, timeout: Duration) -> Self {
+    self.timeout = Some(timeout);
+    self
+  }
+
+  /// Detect obvious non-termination, failing with `EvalError::Diverges` when a
+  /// term reduces to one (up to α) that is still being evaluated
+  /// - only the `window` most recent such terms are remembered and compared
+  pub fn with_cycle_detection(mut self, window: usize) -> Self {
+    self.cycle_window = Some(window);
+    self
+  }
+
+  /// Evaluate the two sides of an application in this order
+  pub fn with_order(mut self, order: EvalOrder) -> Self {
+    self.order = order;
+    self
+  }
+
+  /// Record every redex as it's contracted, see `trace`
+  pub fn with_trace(mut self) -> Self {
+    self.trace = Some(Vec::new());
+    self
+  }
+
+  /// Redexes contracted during the last evaluation, in the order they were
+  /// contracted, empty unless `with_trace` was used
+  pub fn trace(&self) -> &[Term<'static>] {
+    self.trace.as_deref().unwrap_or_default()
+  }
+
+  /// Call `observer` for every β-reduction and substitution as it happens, to
+  /// instrument evaluation from the outside
+  pub fn with_observer(mut self, observer: Observer) -> Self {
+    self.observer = Some(ObserverSlot(observer));
+    self
+  }
+
+  /// Mint the names of α-renamed binders with `namer` rather than by priming
+  pub fn with_name_gen(mut self, namer: impl NameGen + 'static) -> Self {
+    self.namer = Box::new(namer);
+    self
+  }
+
+  /// Remember the normal form of every term evaluated, so evaluating it (or
+  /// anything α-equivalent to it) again is free
+  pub fn with_cache(mut self) -> Self {
+    self.cache = Some(HashMap::new());
+    self
+  }
+
+  /// Evaluate call-by-need, so an argument is only evaluated once it's needed,
+  /// and then at most once, however many times it's used
+  /// - evaluation stops at weak head normal form, though still going on to the
+  ///   full normal form with `with_full_normalization`
+  /// - the evaluation order, cycle detection, trace and observer have no effect,
+  ///   since arguments are never substituted in as terms
+  pub fn with_call_by_need(mut self) -> Self {
+    self.lazy = true;
+    self
+  }
+
+  /// Compute `plus m n` and `mult m n` directly once both operands normalize to
+  /// Church numerals, rather than by β-reduction, where `plus` and `mult` are any
+  /// names `evaluate_in_env` defines as `prelude::plus` or `prelude::mult` (up to α)
+  /// - only under `with_full_normalization`, since otherwise the slow path stops
+  ///   short of a numeral, and the result is the same normal form up to α
+  /// - the β-reductions skipped aren't counted, traced or observed, and don't
+  ///   count towards the step limit
+  pub fn with_numeral_arithmetic(mut self) -> Self {
+    self.arithmetic = Some(HashMap::new());
+    self
+  }
+
+  /// Never apply an abstraction binding one of `params`, leaving an application
+  /// of it stuck as though its function were a variable
+  /// - handy to watch a combinator written `λf. ...` get passed around unreduced
+  /// - has no effect under call-by-need
+  pub fn with_opaque_params<S: Into<String>>(
+    mut self,
+    params: impl IntoIterator<Item = S>,
+  ) -> Self {
+    self.opaque = params.into_iter().map(Into::into).collect();
+    self
+  }
+
+  /// Rename binders primed during α-conversion back to their original names in
+  /// the result, wherever that captures nothing (see `Term::restore_names`)
+  pub fn with_restored_names(mut self) -> Self {
+    self.restore_names = true;
+    self
+  }
+
+  /// Evaluate some term, surfacing any configured limit being hit as an error
+  pub fn try_evaluate(&mut self, term: &Term<'src>) -> Result<Term<'src>, EvalError> {
+    self.stats = EvalStats::default();
+    self.depth = 0;
+    self.pending.clear();
+    self.scope.clear();
+    self.started = self.timeout.map(|_| Instant::now());
+    if let Some(trace) = &mut self.trace {
+      trace.clear();
+    }
+    // key on the canonical form, so names of binders can't affect the lookup
+    // - what free variables mean depends on the environment, so skip caching with one
+    let key = self
+      .cache
+      .as_ref()
+      .filter(|_| self.env.is_empty())
+      .map(|_| term.canonicalize());
+    let result = match key.as_ref().and_then(|key| self.cache.as_ref()?.get(key)) {
+      Some(cached) => cached.clone(),
+      None => {
+        let result = if self.lazy {
+          self.evaluate_lazy(term)?
         } else {
-          // Cannot apply, construct the application with evaluated parts
-          Term::Application {
-            lhs: Box::new(lhs_eval),
-            rhs: Box::new(rhs_eval),
-          }
+          self.evaluate_term(term)?
+        };
+        if let (Some(key), Some(cache)) = (key, self.cache.as_mut()) {
+          cache.insert(key, result.to_owned_term());
         }
+        result
+      }
+    };
+    Ok(if self.restore_names {
+      result.restore_names()
+    } else {
+      result
+    })
+  }
+
+  /// Evaluate some term, resolving free variables named in `env` to their
+  /// definitions
+  /// - a name is only unfolded once it's applied to something, so recursive
+  ///   definitions don't unfold forever, and a binder of the same name shadows it
+  pub fn evaluate_in_env(
+    &mut self,
+    term: &Term<'src>,
+    env: &HashMap<&str, Term<'src>>,
+  ) -> Result<Term<'src>, EvalError> {
+    self.env = env
+      .iter()
+      .map(|(name, definition)| (name.to_string(), definition.to_owned_term()))
+      .collect();
+    if let Some(arithmetic) = &mut self.arithmetic {
+      arithmetic.extend(env.iter().filter_map(|(name, definition)| {
+        Some((name.to_string(), Arithmetic::recognize(definition)?))
+      }));
+    }
+    let result = self.try_evaluate(term);
+    self.env.clear();
+    if let Some(arithmetic) = &mut self.arithmetic {
+      arithmetic.clear();
+    }
+    result
+  }
+
+  /// Evaluate some term, reporting how much work it took to simplify
+  ///
+  /// # Panics
+  /// If a configured limit is hit, use `try_evaluate` to handle that instead
+  pub fn evaluate_with_stats(&mut self, term: &Term<'src>) -> (Term<'src>, EvalStats) {
+    let result = self.try_evaluate(term).expect("evaluation hit a limit");
+    (result, self.stats)
+  }
+
+  /// Fully normalize a term by applying `step` until no redex remains, or until
+  /// `max_steps` have been taken, keeping whatever term was reached rather than
+  /// failing when the cap is hit
+  /// - normal order, so this finds the normal form whenever there is one
+  /// - arguments of a stuck head are reduced once nothing is left further left,
+  ///   the leftmost argument first, so a cap may leave later ones unreduced
+  pub fn normalize(&mut self, term: &Term<'src>, max_steps: usize) -> EvalOutcome<'src> {
+    let mut term = term.clone();
+    let mut steps = 0;
+    while steps < max_steps {
+      match self.step(&term) {
+        Some(next) => term = next,
+        None => break,
       }
-      // Otherwise, return the term as is
-      _ => term.clone(),
+      steps += 1;
+    }
+    EvalOutcome {
+      normal_form: term.is_normal_form(),
+      closed: term.is_closed(),
+      term,
+      steps,
+    }
+  }
+
+  /// Reduce in normal order for at most `fuel` steps, keeping whatever term was
+  /// reached instead of failing when the fuel runs out
+  #[deprecated(note = "renamed to `normalize`")]
+  pub fn evaluate_with_fuel(&mut self, term: &Term<'src>, fuel: usize) -> EvalOutcome<'src> {
+    self.normalize(term, fuel)
+  }
+
+  /// Hash the α-canonical normal form of a term, e.g. to bucket terms by what
+  /// they compute before comparing them properly, or `None` if it wasn't
+  /// reached within the step limit (`DEFAULT_HASH_STEPS` unless set)
+  /// - terms with the same normal form, up to α-equivalence, always hash equal,
+  ///   though distinct normal forms can collide
+  /// - the hash is only stable within one build, so shouldn't be persisted
+  pub fn normal_form_hash(&mut self, term: &Term<'src>) -> Option<u64> {
+    let max_steps = self.max_steps.unwrap_or(DEFAULT_HASH_STEPS);
+    let outcome = self.normalize(term, max_steps);
+    if !outcome.normal_form {
+      return None;
     }
+    let mut hasher = DefaultHasher::new();
+    outcome.term.canonicalize().hash(&mut hasher);
+    Some(hasher.finish())
   }
 
-  /// Substitute occurrences of a variable with a given term
-  fn substitute(&mut self, term: &Term<'src>, var: &'src str, value: &Term<'src>) -> Term<'src> {
+  /// Contract every η-redex, i.e. `λx. f x` into `f` when `x` isn't free in `f`,
+  /// anywhere in the term, giving the η-short form
+  pub fn eta_reduce(&mut self, term: &Term<'src>) -> Term<'src> {
     match term {
-      Term::Variable(name) if *name == var => value.clone(),
       Term::Variable(_) => term.clone(),
-      Term::Abstraction { param, body } if *param != var => Term::Abstraction {
-        param,
-        body: Box::new(self.substitute(body, var, value)),
+      Term::Abstraction { param, body } => {
+        let body = self.eta_reduce(body);
+        match &body {
+          Term::Application { lhs, rhs }
+            if matches!(&**rhs, Term::Variable(name) if name == param)
+              && !lhs.free_variables().contains(param.as_ref()) =>
+          {
+            Term::clone(lhs)
+          }
+          _ => Term::Abstraction {
+            param: param.clone(),
+            body: Rc::new(body),
+          },
+        }
+      }
+      Term::Application { lhs, rhs } => Term::app(self.eta_reduce(lhs), self.eta_reduce(rhs)),
+    }
+  }
+
+  /// Wrap a term in one more binder, turning `f` into `λx. f x` with `x` fresh
+  /// - abstractions are left as they are, there's no type to say how many binders
+  ///   the η-long form should have, so this expands at most once and never loops
+  /// - for a term with no η-redexes, `eta_reduce` undoes it
+  pub fn eta_expand(&mut self, term: &Term<'src>) -> Term<'src> {
+    if let Term::Abstraction { .. } = term {
+      return term.clone();
+    }
+    let param = self.namer.fresh("x", &term.free_variables());
+    Term::abs(param.clone(), Term::app(term.clone(), Term::var(param)))
+  }
+
+  /// Contract exactly one redex, picking the leftmost-outermost one (normal order)
+  /// - returns `None` when there is no redex anywhere, i.e. the term is already in
+  ///   normal form
+  pub fn step(&mut self, term: &Term<'src>) -> Option<Term<'src>> {
+    self.step_at(term, &mut Vec::new())
+  }
+
+  /// Contract redexes one at a time in normal order, like repeatedly calling
+  /// `step`, reporting where each one was
+  pub fn reductions<'a>(&'a mut self, term: &Term<'src>) -> Reductions<'a, 'src> {
+    Reductions {
+      interpreter: self,
+      term: Some(term.clone()),
+    }
+  }
+
+  /// `step`, which leaves `path` leading to the redex it contracted
+  fn step_at(&mut self, term: &Term<'src>, path: &mut Vec<Step>) -> Option<Term<'src>> {
+    match term {
+      Term::Application { lhs, rhs } => {
+        match &**lhs {
+          Term::Abstraction { param, body } if !self.opaque.contains(param.as_ref()) => {
+            self.stats.beta_reductions += 1;
+            let contractum = self.substitute(body, param, rhs);
+            self.notify(ReductionEvent::Beta {
+              redex: term,
+              contractum: &contractum,
+            });
+            return Some(contractum);
+          }
+          _ => {}
+        }
+        // the function position is further left, so try it first
+        path.push(Step::Lhs);
+        if let Some(lhs) = self.step_at(lhs, path) {
+          return Some(Term::Application {
+            lhs: Rc::new(lhs),
+            rhs: rhs.clone(),
+          });
+        }
+        path.pop();
+        path.push(Step::Rhs);
+        let stepped = self.step_at(rhs, path).map(|rhs| Term::Application {
+          lhs: lhs.clone(),
+          rhs: Rc::new(rhs),
+        });
+        if stepped.is_none() {
+          path.pop();
+        }
+        stepped
+      }
+      Term::Abstraction { param, body } => {
+        path.push(Step::Body);
+        let stepped = self.step_at(body, path).map(|body| Term::Abstraction {
+          param: param.clone(),
+          body: Rc::new(body),
+        });
+        if stepped.is_none() {
+          path.pop();
+        }
+        stepped
+      }
+      Term::Variable(_) => None,
+    }
+  }
+
+  /// Recursively simplify a given term to evaluate it
+  fn evaluate_term(&mut self, term: &Term<'src>) -> Result<Term<'src>, EvalError> {
+    if self.max_depth.is_some_and(|max| self.depth >= max) {
+      return Err(EvalError::RecursionLimit);
+    }
+    self.depth += 1;
+    self.stats.max_depth = self.stats.max_depth.max(self.depth);
+    // a contractum in tail position is carried on with here rather than by
+    // recursing, so a divergent term like Ω loops rather than using up the stack
+    let mut contractum = None;
+    let mut entered = 0;
+    let result = loop {
+      let term = contractum.as_ref().unwrap_or(term);
+      break match term {
+        Term::Application { .. } if self.arithmetic_on(term).is_some() => self.arithmetic(term)?,
+        // Evaluate applications
+        Term::Application { lhs, rhs } => {
+          let (lhs_eval, rhs_eval) = match self.order {
+            EvalOrder::FunctionFirst => {
+              let lhs_eval = self.evaluate_term(lhs)?;
+              let lhs_eval = self.unfold(lhs_eval)?;
+              (lhs_eval, self.evaluate_term(rhs)?)
+            }
+            EvalOrder::ArgumentFirst => {
+              let rhs_eval = self.evaluate_term(rhs)?;
+              let lhs_eval = self.evaluate_term(lhs)?;
+              (self.unfold(lhs_eval)?, rhs_eval)
+            }
+          };
+          // Apply the abstraction if the left-hand side is one
+          match self.contract(&lhs_eval, &rhs_eval)? {
+            Some(subs) => {
+              contractum = Some(subs);
+              entered += 1;
+              continue;
+            }
+            // Cannot apply, construct the application with evaluated parts
+            None => Term::Application {
+              lhs: Rc::new(lhs_eval),
+              rhs: Rc::new(rhs_eval),
+            },
+          }
+        }
+        // Only look inside abstractions when asked to
+        Term::Abstraction { param, body } if self.full => {
+          let shadows = !self.env.is_empty();
+          if shadows {
+            self.scope.push(param.to_string());
+          }
+          let body = self.evaluate_term(body)?;
+          if shadows {
+            self.scope.pop();
+          }
+          Term::Abstraction {
+            param: param.clone(),
+            body: Rc::new(body),
+          }
+        }
+        // Otherwise, return the term as is
+        _ => term.clone(),
+      };
+    };
+    // every contractum carried on with was entered, and is done with now
+    for _ in 0..entered {
+      self.pending.pop();
+    }
+    self.depth -= 1;
+    Ok(result)
+  }
+
+  /// Contract `lhs_eval rhs_eval` if it's a redex, giving the contractum to carry
+  /// on evaluating
+  /// - kept out of `evaluate_term` so its stack frame stays small
+  fn contract(
+    &mut self,
+    lhs_eval: &Term<'src>,
+    rhs_eval: &Term<'src>,
+  ) -> Result<Option<Term<'src>>, EvalError> {
+    match lhs_eval {
+      Term::Abstraction { param, body } if !self.opaque.contains(param.as_ref()) => {
+        let () = self.count_step()?;
+        self.record(lhs_eval, rhs_eval);
+        let subs = self.substitute(body, param, rhs_eval);
+        self.notify_beta(lhs_eval, rhs_eval, &subs);
+        let () = self.check_size(&subs)?;
+        let () = self.enter(&subs)?;
+        Ok(Some(subs))
+      }
+      _ => Ok(None),
+    }
+  }
+
+  /// A free name being applied might have a definition to unfold, in which case
+  /// evaluate that in its place
+  /// - kept out of `evaluate_term` (as is `record`) so its stack frame stays small
+  fn unfold(&mut self, lhs_eval: Term<'src>) -> Result<Term<'src>, EvalError> {
+    let Term::Variable(name) = &lhs_eval else {
+      return Ok(lhs_eval);
+    };
+    if self.scope.iter().any(|bound| bound == name) {
+      return Ok(lhs_eval);
+    }
+    let Some(definition) = self.env.get(name.as_ref()).cloned() else {
+      return Ok(lhs_eval);
+    };
+    // only far enough to expose an abstraction, fully normalizing a recursive
+    // definition would unfold it forever
+    let full = std::mem::replace(&mut self.full, false);
+    let definition = self.evaluate_term(&definition);
+    self.full = full;
+    definition
+  }
+
+  /// The arithmetic combinator `term` applies to two operands, if it's one
+  /// `with_numeral_arithmetic` can compute directly
+  fn arithmetic_on(&self, term: &Term<'src>) -> Option<Arithmetic> {
+    let arithmetic = self.arithmetic.as_ref().filter(|_| self.full)?;
+    let Term::Application { lhs, .. } = term else {
+      return None;
+    };
+    let Term::Application { lhs: op, .. } = &**lhs else {
+      return None;
+    };
+    let Term::Variable(name) = &**op else {
+      return None;
+    };
+    if self.scope.iter().any(|bound| bound == name) {
+      return None;
+    }
+    arithmetic.get(name.as_ref()).copied()
+  }
+
+  /// Normalize both operands of an arithmetic combinator, computing the result
+  /// directly if they're numerals and reducing as usual otherwise
+  fn arithmetic(&mut self, term: &Term<'src>) -> Result<Term<'src>, EvalError> {
+    let Some(arithmetic) = self.arithmetic_on(term) else {
+      unreachable!("only called on an arithmetic application");
+    };
+    let (op, operands) = term.spine();
+    let (m, n) = match self.order {
+      EvalOrder::FunctionFirst => {
+        let m = self.evaluate_term(operands[0])?;
+        (m, self.evaluate_term(operands[1])?)
+      }
+      EvalOrder::ArgumentFirst => {
+        let n = self.evaluate_term(operands[1])?;
+        (self.evaluate_term(operands[0])?, n)
+      }
+    };
+    let computed = m
+      .as_church_numeral()
+      .zip(n.as_church_numeral())
+      .and_then(|(m, n)| arithmetic.apply(m, n));
+    if let Some(value) = computed {
+      return Ok(prelude::numeral(value));
+    }
+    // the operands are already normal, so this can't recognize them again
+    let fast = self.arithmetic.take();
+    let result = self.evaluate_term(&Term::from_spine(op.clone(), [m, n]));
+    self.arithmetic = fast;
+    result
+  }
+
+  /// Count a β-reduction about to be performed, failing if that goes over the
+  /// step limit or evaluation has run out of time
+  fn count_step(&mut self) -> Result<(), EvalError> {
+    if self
+      .max_steps
+      .is_some_and(|max| self.stats.beta_reductions >= max)
+    {
+      return Err(EvalError::StepLimitExceeded);
+    }
+    self.stats.beta_reductions += 1;
+    if self
+      .stats
+      .beta_reductions
+      .is_multiple_of(TIMEOUT_CHECK_INTERVAL)
+    {
+      if let (Some(started), Some(timeout)) = (self.started, self.timeout) {
+        if started.elapsed() > timeout {
+          return Err(EvalError::Timeout);
+        }
+      }
+    }
+    Ok(())
+  }
+
+  /// Add a redex about to be contracted to the trace, if there is one
+  fn record(&mut self, lhs: &Term<'src>, rhs: &Term<'src>) {
+    if let Some(trace) = &mut self.trace {
+      trace.push(Term::app(lhs.clone(), rhs.clone()).to_owned_term());
+    }
+  }
+
+  /// Tell the observer, if there is one, about a redex built from its two sides
+  fn notify_beta(&mut self, lhs: &Term<'src>, rhs: &Term<'src>, contractum: &Term<'src>) {
+    if self.observer.is_some() {
+      let redex = Term::app(lhs.clone(), rhs.clone());
+      self.notify(ReductionEvent::Beta {
+        redex: &redex,
+        contractum,
+      });
+    }
+  }
+
+  fn notify(&mut self, event: ReductionEvent<'_>) {
+    if let Some(ObserverSlot(observer)) = &mut self.observer {
+      observer(&event);
+    }
+  }
+
+  /// Fail if a contractum is bigger than the size limit allows
+  fn check_size(&self, term: &Term<'src>) -> Result<(), EvalError> {
+    match self.max_term_size {
+      Some(max) if term.size() > max => Err(EvalError::TermTooLarge),
+      _ => Ok(()),
+    }
+  }
+
+  /// Record that some contractum is about to be evaluated, failing if an
+  /// α-equivalent term is already being evaluated further up
+  fn enter(&mut self, term: &Term<'src>) -> Result<(), EvalError> {
+    let Some(window) = self.cycle_window else {
+      return Ok(());
+    };
+    let recent = self.pending.len().saturating_sub(window);
+    let cycle = self.pending[recent..]
+      .iter()
+      .flatten()
+      .any(|seen| seen.alpha_eq(term));
+    if cycle {
+      return Err(EvalError::Diverges);
+    }
+    // forget whatever just fell out of the window
+    let stale = self.pending.len().checked_sub(window);
+    if let Some(slot) = stale.and_then(|idx| self.pending.get_mut(idx)) {
+      *slot = None;
+    }
+    self
+      .pending
+      .push((window > 0).then(|| term.to_owned_term()));
+    Ok(())
+  }
+
+  /// Substitute free occurrences of a variable with a given term
+  fn substitute(&mut self, term: &Term<'src>, var: &str, value: &Term<'src>) -> Term<'src> {
+    let result = term.substitute_with(var, value, &mut *self.namer, &mut self.stats.substitutions);
+    self.notify(ReductionEvent::Substitution {
+      term,
+      var,
+      value,
+      result: &result,
+    });
+    result
+  }
+}
+
+/// The bindings in scope during call-by-need evaluation, innermost first
+type Env<'src> = Option<Rc<Binding<'src>>>;
+
+struct Binding<'src> {
+  name: Cow<'src, str>,
+  thunk: Rc<RefCell<Thunk<'src>>>,
+  next: Env<'src>,
+}
+
+impl Drop for Binding<'_> {
+  /// Unlink whatever only this binding keeps alive one piece at a time, as a loop
+  /// evaluating in constant stack can build chains of bindings and thunks far
+  /// longer than dropping them recursively could get through
+  fn drop(&mut self) {
+    let mut envs = vec![self.next.take()];
+    let mut thunks = Vec::new();
+    unlink(&self.thunk, &mut envs, &mut thunks);
+    loop {
+      if let Some(env) = envs.pop() {
+        if let Some(Ok(mut binding)) = env.map(Rc::try_unwrap) {
+          envs.push(binding.next.take());
+          unlink(&binding.thunk, &mut envs, &mut thunks);
+        }
+      } else if let Some(thunk) = thunks.pop() {
+        unlink(&thunk, &mut envs, &mut thunks);
+      } else {
+        break;
+      }
+    }
+  }
+}
+
+/// Move out what a thunk refers to, if nothing else shares the thunk, so it can be
+/// dropped by the loop in `Binding::drop` instead
+fn unlink<'src>(
+  thunk: &Rc<RefCell<Thunk<'src>>>,
+  envs: &mut Vec<Env<'src>>,
+  thunks: &mut Vec<Rc<RefCell<Thunk<'src>>>>,
+) {
+  if Rc::strong_count(thunk) != 1 {
+    return;
+  }
+  match &mut *thunk.borrow_mut() {
+    Thunk::Delayed(_, env) | Thunk::Forced(Value::Closure { env, .. }) => envs.push(env.take()),
+    Thunk::Forced(Value::Neutral { args, .. }) => thunks.append(args),
+  }
+}
+
+/// An argument, shared by every occurrence of the variable it's bound to
+enum Thunk<'src> {
+  /// Not needed yet, along with the bindings it was written under
+  Delayed(Term<'src>, Env<'src>),
+  Forced(Value<'src>),
+}
+
+/// What call-by-need evaluation produces: a weak head normal form
+#[derive(Clone)]
+enum Value<'src> {
+  Closure {
+    param: Cow<'src, str>,
+    body: Rc<Term<'src>>,
+    env: Env<'src>,
+  },
+  /// A free variable applied to (still unevaluated) arguments
+  Neutral {
+    head: Cow<'src, str>,
+    args: Vec<Rc<RefCell<Thunk<'src>>>>,
+  },
+}
+
+fn lookup<'a, 'src>(env: &'a Env<'src>, name: &str) -> Option<&'a Rc<RefCell<Thunk<'src>>>> {
+  let mut env = env.as_ref();
+  while let Some(binding) = env {
+    if binding.name == name {
+      return Some(&binding.thunk);
+    }
+    env = binding.next.as_ref();
+  }
+  None
+}
+
+impl<'src> Interpreter {
+  /// Evaluate call-by-need, reading the result back into a term
+  fn evaluate_lazy(&mut self, term: &Term<'src>) -> Result<Term<'src>, EvalError> {
+    let value = self.eval_need(term, &None)?;
+    let result = self.read_back(value)?;
+    let () = self.check_size(&result)?;
+    if self.full {
+      self.evaluate_term(&result)
+    } else {
+      Ok(result)
+    }
+  }
+
+  fn eval_need(&mut self, term: &Term<'src>, env: &Env<'src>) -> Result<Value<'src>, EvalError> {
+    if self.max_depth.is_some_and(|max| self.depth >= max) {
+      return Err(EvalError::RecursionLimit);
+    }
+    self.depth += 1;
+    self.stats.max_depth = self.stats.max_depth.max(self.depth);
+    let mut body: Option<(Rc<Term<'src>>, Env<'src>)> = None;
+    let value = loop {
+      let (term, env) = match &body {
+        Some((term, env)) => (&**term, env),
+        None => (term, env),
+      };
+      match self.eval_need_once(term, env)? {
+        Tail::Done(value) => break value,
+        Tail::Continue(next) => body = Some(next),
+      }
+    };
+    self.depth -= 1;
+    Ok(value)
+  }
+
+  /// The body of `eval_need`, handing back an abstraction body in tail position
+  /// (with the environment to evaluate it in) rather than evaluating it
+  fn eval_need_once(
+    &mut self,
+    term: &Term<'src>,
+    env: &Env<'src>,
+  ) -> Result<Tail<Value<'src>, (Rc<Term<'src>>, Env<'src>)>, EvalError> {
+    let value = match term {
+      Term::Variable(name) => match lookup(env, name) {
+        Some(thunk) => self.force(&Rc::clone(thunk))?,
+        None => match self.env.get(name.as_ref()).cloned() {
+          // definitions only see each other, not whatever binders are in scope here
+          Some(definition) => self.eval_need(&definition, &None)?,
+          None => Value::Neutral {
+            head: name.clone(),
+            args: Vec::new(),
+          },
+        },
       },
-      Term::Application { lhs, rhs } => Term::Application {
-        lhs: Box::new(self.substitute(lhs, var, value)),
-        rhs: Box::new(self.substitute(rhs, var, value)),
+      Term::Abstraction { param, body } => Value::Closure {
+        param: param.clone(),
+        body: Rc::clone(body),
+        env: env.clone(),
       },
-      // nothing to substitute
-      _ => term.clone(),
+      Term::Application { lhs, rhs } => {
+        let arg = Rc::new(RefCell::new(Thunk::Delayed(Term::clone(rhs), env.clone())));
+        match self.eval_need(lhs, env)? {
+          Value::Closure { param, body, env } => {
+            let () = self.count_step()?;
+            let env = Some(Rc::new(Binding {
+              name: param,
+              thunk: arg,
+              next: env,
+            }));
+            return Ok(Tail::Continue((body, env)));
+          }
+          Value::Neutral { head, mut args } => {
+            args.push(arg);
+            Value::Neutral { head, args }
+          }
+        }
+      }
+    };
+    Ok(Tail::Done(value))
+  }
+
+  /// Evaluate an argument the first time it's needed, remembering its value
+  fn force(&mut self, thunk: &Rc<RefCell<Thunk<'src>>>) -> Result<Value<'src>, EvalError> {
+    let (term, env) = match &*thunk.borrow() {
+      Thunk::Forced(value) => return Ok(value.clone()),
+      Thunk::Delayed(term, env) => (term.clone(), env.clone()),
+    };
+    let value = self.eval_need(&term, &env)?;
+    *thunk.borrow_mut() = Thunk::Forced(value.clone());
+    Ok(value)
+  }
+
+  /// Turn a value back into a term, substituting in whatever its bindings stand for
+  /// - arguments of a neutral term are evaluated, as they would be otherwise
+  fn read_back(&mut self, value: Value<'src>) -> Result<Term<'src>, EvalError> {
+    match value {
+      Value::Closure { param, body, env } => self.close(&Term::Abstraction { param, body }, &env),
+      Value::Neutral { head, args } => {
+        let args = args
+          .iter()
+          .map(|arg| {
+            let value = self.force(arg)?;
+            self.read_back(value)
+          })
+          .collect::<Result<Vec<_>, _>>()?;
+        Ok(Term::from_spine(Term::Variable(head), args))
+      }
+    }
+  }
+
+  /// Substitute every free variable of `term` bound in `env`, without evaluating
+  /// any arguments that haven't been needed yet
+  fn close(&mut self, term: &Term<'src>, env: &Env<'src>) -> Result<Term<'src>, EvalError> {
+    let mut bound = Vec::new();
+    for name in term.free_variables() {
+      let Some(thunk) = lookup(env, name).cloned() else {
+        continue;
+      };
+      let value = match &*thunk.borrow() {
+        Thunk::Forced(value) => Ok(value.clone()),
+        Thunk::Delayed(term, env) => Err((term.clone(), env.clone())),
+      };
+      let value = match value {
+        Ok(value) => self.read_back(value)?,
+        Err((term, env)) => self.close(&term, &env)?,
+      };
+      bound.push((name.to_string(), value));
+    }
+    // a value can mention a name bound here too (meaning something else), so move
+    // every bound name out of the way before substituting any value in
+    let mut used: HashSet<String> = term
+      .free_variables()
+      .into_iter()
+      .map(String::from)
+      .collect();
+    for (_, value) in &bound {
+      used.extend(value.free_variables().into_iter().map(String::from));
+    }
+    let mut closed = term.clone();
+    let mut placeholders = Vec::new();
+    for (name, value) in bound {
+      let placeholder = self
+        .namer
+        .fresh(&name, &used.iter().map(String::as_str).collect());
+      used.insert(placeholder.clone());
+      closed = closed.substitute_with(
+        &name,
+        &Term::var(placeholder.clone()),
+        &mut *self.namer,
+        &mut 0,
+      );
+      placeholders.push((placeholder, value));
     }
+    for (placeholder, value) in placeholders {
+      closed = closed.substitute_with(
+        &placeholder,
+        &value,
+        &mut *self.namer,
+        &mut self.stats.substitutions,
+      );
+    }
+    Ok(closed)
   }
 }
 
 impl<'src> Evaluate<'src> for Interpreter {
-  /// Simplify some term using α-conversion, β-reduction, and η-reduction
+  /// Simplify some term using α-conversion and β-reduction, see `eta_reduce` for
+  /// η-reduction
+  ///
+  /// # Panics
+  /// If a configured limit is hit, use `Interpreter::try_evaluate` to handle that instead
   fn evaluate(&mut self, term: &Term<'src>) -> Term<'src> {
-    self.evaluate_term(term)
+    self.try_evaluate(term).expect("evaluation hit a limit")
+  }
+
+  fn try_evaluate(&mut self, term: &Term<'src>) -> Result<Term<'src>, EvalError> {
+    Interpreter::try_evaluate(self, term)
   }
 }
 
@@ -65,13 +1109,22 @@ mod tests {
   mod interpreter {
     use super::*;
 
+    use std::collections::HashSet;
+
+    use crate::api::parse_str;
+    use crate::prelude;
+
+    fn parse(input: &str) -> Term<'_> {
+      parse_str(input).expect("test input should parse")
+    }
+
     #[test]
     fn evaluate_abstraction_identity() {
       let term = Term::Abstraction {
-        param: "x",
-        body: Box::new(Term::Variable("x")),
+        param: "x".into(),
+        body: Rc::new(Term::Variable("x".into())),
       };
-      let mut interpreter = Interpreter;
+      let mut interpreter = Interpreter::new();
       let result = interpreter.evaluate(&term);
       // Abstractions evaluate to themselves
       assert_eq!(result, term);
@@ -80,206 +1133,195 @@ mod tests {
     #[test]
     fn evaluate_simple_application() {
       let term = Term::Application {
-        lhs: Box::new(Term::Abstraction {
-          param: "x",
-          body: Box::new(Term::Variable("x")),
+        lhs: Rc::new(Term::Abstraction {
+          param: "x".into(),
+          body: Rc::new(Term::Variable("x".into())),
         }),
-        rhs: Box::new(Term::Variable("y")),
+        rhs: Rc::new(Term::Variable("y".into())),
       };
-      let mut interpreter = Interpreter;
+      let mut interpreter = Interpreter::new();
       let result = interpreter.evaluate(&term);
       // (λx. x) y produces y
-      assert_eq!(result, Term::Variable("y"));
+      assert_eq!(result, Term::Variable("y".into()));
     }
 
     #[test]
     fn evaluate_nested_application() {
       let term = Term::Application {
-        lhs: Box::new(Term::Application {
-          lhs: Box::new(Term::Abstraction {
-            param: "x",
-            body: Box::new(Term::Abstraction {
-              param: "y",
-              body: Box::new(Term::Variable("x")),
+        lhs: Rc::new(Term::Application {
+          lhs: Rc::new(Term::Abstraction {
+            param: "x".into(),
+            body: Rc::new(Term::Abstraction {
+              param: "y".into(),
+              body: Rc::new(Term::Variable("x".into())),
             }),
           }),
-          rhs: Box::new(Term::Variable("a")),
+          rhs: Rc::new(Term::Variable("a".into())),
         }),
-        rhs: Box::new(Term::Variable("b")),
+        rhs: Rc::new(Term::Variable("b".into())),
       };
-      let mut interpreter = Interpreter;
+      let mut interpreter = Interpreter::new();
       let result = interpreter.evaluate(&term);
       // ((λx. λy. x) a) b produces a
-      assert_eq!(result, Term::Variable("a"));
+      assert_eq!(result, Term::Variable("a".into()));
+    }
+
+    #[test]
+    fn evaluate_with_stats_counts_reductions() {
+      let term = Term::Application {
+        lhs: Rc::new(Term::Application {
+          lhs: Rc::new(Term::Abstraction {
+            param: "x".into(),
+            body: Rc::new(Term::Abstraction {
+              param: "y".into(),
+              body: Rc::new(Term::Variable("x".into())),
+            }),
+          }),
+          rhs: Rc::new(Term::Variable("a".into())),
+        }),
+        rhs: Rc::new(Term::Variable("b".into())),
+      };
+      let mut interpreter = Interpreter::new();
+      let (result, stats) = interpreter.evaluate_with_stats(&term);
+      assert_eq!(result, Term::Variable("a".into()));
+      // one reduction per argument, only `x` is ever replaced
+      assert_eq!(stats.beta_reductions, 2);
+      assert_eq!(stats.substitutions, 1);
+      assert_eq!(stats.max_depth, 3);
     }
 
     #[test]
     fn evaluate_and() {
       // λp. λq. p q p
       let and_term = Term::Abstraction {
-        param: "p",
-        body: Box::new(Term::Abstraction {
-          param: "q",
-          body: Box::new(Term::Application {
-            lhs: Box::new(Term::Application {
-              lhs: Box::new(Term::Variable("p")),
-              rhs: Box::new(Term::Variable("q")),
+        param: "p".into(),
+        body: Rc::new(Term::Abstraction {
+          param: "q".into(),
+          body: Rc::new(Term::Application {
+            lhs: Rc::new(Term::Application {
+              lhs: Rc::new(Term::Variable("p".into())),
+              rhs: Rc::new(Term::Variable("q".into())),
             }),
-            rhs: Box::new(Term::Variable("p")),
+            rhs: Rc::new(Term::Variable("p".into())),
           }),
         }),
       };
       // λx. λy. x
       let true_term = Term::Abstraction {
-        param: "x",
-        body: Box::new(Term::Abstraction {
-          param: "y",
-          body: Box::new(Term::Variable("x")),
+        param: "x".into(),
+        body: Rc::new(Term::Abstraction {
+          param: "y".into(),
+          body: Rc::new(Term::Variable("x".into())),
         }),
       };
       // λx. λy. y
       let false_term = Term::Abstraction {
-        param: "x",
-        body: Box::new(Term::Abstraction {
-          param: "y",
-          body: Box::new(Term::Variable("y")),
+        param: "x".into(),
+        body: Rc::new(Term::Abstraction {
+          param: "y".into(),
+          body: Rc::new(Term::Variable("y".into())),
         }),
       };
 
       // (λp. λq. p q p) (λx. λy. x) (λx. λy. y)
       let term = Term::Application {
-        lhs: Box::new(Term::Application {
-          lhs: Box::new(and_term),
-          rhs: Box::new(true_term.clone()),
+        lhs: Rc::new(Term::Application {
+          lhs: Rc::new(and_term),
+          rhs: Rc::new(true_term.clone()),
         }),
-        rhs: Box::new(false_term.clone()),
+        rhs: Rc::new(false_term.clone()),
       };
 
-      let mut interpreter = Interpreter;
+      let mut interpreter = Interpreter::new();
       let result = interpreter.evaluate(&term);
       // true AND false produces false
       assert_eq!(result, false_term);
+      assert_eq!(result.as_church_bool(), Some(false));
     }
 
     #[test]
     fn evaluate_or() {
-      // λp. λq. p p q
-      let or_term = Term::Abstraction {
-        param: "p",
-        body: Box::new(Term::Abstraction {
-          param: "q",
-          body: Box::new(Term::Application {
-            lhs: Box::new(Term::Application {
-              lhs: Box::new(Term::Variable("p")),
-              rhs: Box::new(Term::Variable("p")),
+      use crate::testing::assert_evaluates_to;
+
+      // false or true produces true
+      assert_evaluates_to("(λp. λq. p p q) (λx. λy. y) (λx. λy. x)", "λx. λy. x");
+      // and either way round
+      assert_evaluates_to("(λp. λq. p p q) (λx. λy. x) (λx. λy. y)", "λx. λy. x");
+      assert_evaluates_to("(λp. λq. p p q) (λx. λy. y) (λx. λy. y)", "λx. λy. y");
+    }
+
+    #[test]
+    fn evaluate_not() {
+      // λp. p (λx. λy. y) (λx. λy. x)
+      let not_term = Term::Abstraction {
+        param: "p".into(),
+        body: Rc::new(Term::Application {
+          lhs: Rc::new(Term::Application {
+            lhs: Rc::new(Term::Variable("p".into())),
+            rhs: Rc::new(Term::Abstraction {
+              param: "x".into(),
+              body: Rc::new(Term::Abstraction {
+                param: "y".into(),
+                body: Rc::new(Term::Variable("y".into())),
+              }),
+            }),
+          }),
+          rhs: Rc::new(Term::Abstraction {
+            param: "x".into(),
+            body: Rc::new(Term::Abstraction {
+              param: "y".into(),
+              body: Rc::new(Term::Variable("x".into())),
             }),
-            rhs: Box::new(Term::Variable("q")),
           }),
         }),
       };
       // λx. λy. x
       let true_term = Term::Abstraction {
-        param: "x",
-        body: Box::new(Term::Abstraction {
-          param: "y",
-          body: Box::new(Term::Variable("x")),
+        param: "x".into(),
+        body: Rc::new(Term::Abstraction {
+          param: "y".into(),
+          body: Rc::new(Term::Variable("x".into())),
         }),
       };
       // λx. λy. y
       let false_term = Term::Abstraction {
-        param: "x",
-        body: Box::new(Term::Abstraction {
-          param: "y",
-          body: Box::new(Term::Variable("y")),
+        param: "x".into(),
+        body: Rc::new(Term::Abstraction {
+          param: "y".into(),
+          body: Rc::new(Term::Variable("y".into())),
         }),
       };
 
-      // (λp. λq. p p q) (λx. λy. y) (λx. λy. x)
+      // (λp. p (λx. λy. y) (λx. λy. x)) (λx. λy. x)
       let term = Term::Application {
-        lhs: Box::new(Term::Application {
-          lhs: Box::new(or_term),
-          rhs: Box::new(false_term.clone()),
-        }),
-        rhs: Box::new(true_term.clone()),
+        lhs: Rc::new(not_term),
+        rhs: Rc::new(true_term.clone()),
       };
 
-      let mut interpreter = Interpreter;
+      let mut interpreter = Interpreter::new();
       let result = interpreter.evaluate(&term);
-      // false or true produces true
-      assert_eq!(result, true_term);
-    }
-
-    #[test]
-    fn evaluate_not() {
-      // λp. p (λx. λy. y) (λx. λy. x)
-      let not_term = Term::Abstraction {
-        param: "p",
-        body: Box::new(Term::Application {
-          lhs: Box::new(Term::Application {
-            lhs: Box::new(Term::Variable("p")),
-            rhs: Box::new(Term::Abstraction {
-              param: "x",
-              body: Box::new(Term::Abstraction {
-                param: "y",
-                body: Box::new(Term::Variable("y")),
-              }),
-            }),
-          }),
-          rhs: Box::new(Term::Abstraction {
-            param: "x",
-            body: Box::new(Term::Abstraction {
-              param: "y",
-              body: Box::new(Term::Variable("x")),
-            }),
-          }),
-        }),
-      };
-      // λx. λy. x
-      let true_term = Term::Abstraction {
-        param: "x",
-        body: Box::new(Term::Abstraction {
-          param: "y",
-          body: Box::new(Term::Variable("x")),
-        }),
-      };
-      // λx. λy. y
-      let false_term = Term::Abstraction {
-        param: "x",
-        body: Box::new(Term::Abstraction {
-          param: "y",
-          body: Box::new(Term::Variable("y")),
-        }),
-      };
-
-      // (λp. p (λx. λy. y) (λx. λy. x)) (λx. λy. x)
-      let term = Term::Application {
-        lhs: Box::new(not_term),
-        rhs: Box::new(true_term.clone()),
-      };
-
-      let mut interpreter = Interpreter;
-      let result = interpreter.evaluate(&term);
-      // NOT true produces false
-      assert_eq!(result, false_term);
+      // NOT true produces false
+      assert_eq!(result, false_term);
+      assert_eq!(result.as_church_bool(), Some(false));
     }
 
     #[test]
     fn substitute_variable() {
-      let term = Term::Variable("x");
-      let mut interpreter = Interpreter;
-      let substituted = interpreter.substitute(&term, "x", &Term::Variable("y"));
+      let term = Term::Variable("x".into());
+      let mut interpreter = Interpreter::new();
+      let substituted = interpreter.substitute(&term, "x", &Term::Variable("y".into()));
       // x[x := y] assigns to y
-      assert_eq!(substituted, Term::Variable("y"));
+      assert_eq!(substituted, Term::Variable("y".into()));
     }
 
     #[test]
     fn substitute_abstraction_no_capture() {
       let term = Term::Abstraction {
-        param: "x",
-        body: Box::new(Term::Variable("x")),
+        param: "x".into(),
+        body: Rc::new(Term::Variable("x".into())),
       };
-      let mut interpreter = Interpreter;
-      let substituted = interpreter.substitute(&term, "y", &Term::Variable("z"));
+      let mut interpreter = Interpreter::new();
+      let substituted = interpreter.substitute(&term, "y", &Term::Variable("z".into()));
       // λx. x[y := z] assigns to λx. x
       assert_eq!(substituted, term);
     }
@@ -287,17 +1329,35 @@ mod tests {
     #[test]
     fn substitute_abstraction_capture_internal() {
       let term = Term::Abstraction {
-        param: "x",
-        body: Box::new(Term::Variable("y")),
+        param: "x".into(),
+        body: Rc::new(Term::Variable("y".into())),
       };
-      let mut interpreter = Interpreter;
-      let substituted = interpreter.substitute(&term, "y", &Term::Variable("z"));
+      let mut interpreter = Interpreter::new();
+      let substituted = interpreter.substitute(&term, "y", &Term::Variable("z".into()));
       // λx. y[y := z] assigns to λx. z
       assert_eq!(
         substituted,
         Term::Abstraction {
-          param: "x",
-          body: Box::new(Term::Variable("z")),
+          param: "x".into(),
+          body: Rc::new(Term::Variable("z".into())),
+        }
+      );
+    }
+
+    #[test]
+    fn substitute_abstraction_avoids_capture() {
+      let term = Term::Abstraction {
+        param: "y".into(),
+        body: Rc::new(Term::Variable("x".into())),
+      };
+      let mut interpreter = Interpreter::new();
+      let substituted = interpreter.substitute(&term, "x", &Term::Variable("y".into()));
+      // λy. x[x := y] renames the binder, giving λy'. y
+      assert_eq!(
+        substituted,
+        Term::Abstraction {
+          param: "y'".into(),
+          body: Rc::new(Term::Variable("y".into())),
         }
       );
     }
@@ -305,19 +1365,899 @@ mod tests {
     #[test]
     fn substitute_application() {
       let term = Term::Application {
-        lhs: Box::new(Term::Variable("x")),
-        rhs: Box::new(Term::Variable("y")),
+        lhs: Rc::new(Term::Variable("x".into())),
+        rhs: Rc::new(Term::Variable("y".into())),
       };
-      let mut interpreter = Interpreter;
-      let substituted = interpreter.substitute(&term, "x", &Term::Variable("z"));
+      let mut interpreter = Interpreter::new();
+      let substituted = interpreter.substitute(&term, "x", &Term::Variable("z".into()));
       // (x y)[x := z] assigns to z y
       assert_eq!(
         substituted,
         Term::Application {
-          lhs: Box::new(Term::Variable("z")),
-          rhs: Box::new(Term::Variable("y")),
+          lhs: Rc::new(Term::Variable("z".into())),
+          rhs: Rc::new(Term::Variable("y".into())),
         }
       );
     }
+
+    /// (λx. x x) (λx. x x)
+    fn omega() -> Term<'static> {
+      let half = Term::abs("x", Term::app(Term::var("x"), Term::var("x")));
+      Term::app(half.clone(), half)
+    }
+
+    #[test]
+    fn try_evaluate_step_limit() {
+      let mut interpreter = Interpreter::new().with_max_steps(100);
+      assert_eq!(
+        interpreter.try_evaluate(&omega()),
+        Err(EvalError::StepLimitExceeded)
+      );
+    }
+
+    #[test]
+    fn try_evaluate_within_step_limit() {
+      // (λx. λy. x) a b takes exactly two steps
+      let term = Term::apps([
+        Term::abs("x", Term::abs("y", Term::var("x"))),
+        Term::var("a"),
+        Term::var("b"),
+      ])
+      .unwrap();
+      let mut interpreter = Interpreter::new().with_max_steps(2);
+      assert_eq!(interpreter.try_evaluate(&term), Ok(Term::var("a")));
+      let mut interpreter = Interpreter::new().with_max_steps(1);
+      assert_eq!(
+        interpreter.try_evaluate(&term),
+        Err(EvalError::StepLimitExceeded)
+      );
+    }
+
+    #[test]
+    fn try_evaluate_detects_omega() {
+      let mut interpreter = Interpreter::new().with_cycle_detection(8);
+      assert_eq!(interpreter.try_evaluate(&omega()), Err(EvalError::Diverges));
+    }
+
+    #[test]
+    fn try_evaluate_cycle_detection_normalizing() {
+      // (λf. f (f y)) (λx. x) contracts to `y` twice, but never while already
+      // evaluating `y`, so it must not be mistaken for a loop
+      let term = Term::app(
+        Term::abs(
+          "f",
+          Term::app(Term::var("f"), Term::app(Term::var("f"), Term::var("y"))),
+        ),
+        Term::abs("x", Term::var("x")),
+      );
+      let mut interpreter = Interpreter::new().with_cycle_detection(8);
+      assert_eq!(interpreter.try_evaluate(&term), Ok(Term::var("y")));
+    }
+
+    #[test]
+    fn try_evaluate_zero_window_never_detects() {
+      let mut interpreter = Interpreter::new()
+        .with_cycle_detection(0)
+        .with_max_steps(10);
+      assert_eq!(
+        interpreter.try_evaluate(&omega()),
+        Err(EvalError::StepLimitExceeded)
+      );
+    }
+
+    #[test]
+    fn try_evaluate_cached() {
+      // (λx. λy. x) a b
+      let term = Term::apps([
+        Term::abs("x", Term::abs("y", Term::var("x"))),
+        Term::var("a"),
+        Term::var("b"),
+      ])
+      .unwrap();
+      let mut interpreter = Interpreter::new().with_cache();
+      let (first, stats) = interpreter.evaluate_with_stats(&term);
+      assert_eq!(stats.beta_reductions, 2);
+      let (second, stats) = interpreter.evaluate_with_stats(&term);
+      assert_eq!(second, first);
+      // served from the cache, no work was done
+      assert_eq!(stats.beta_reductions, 0);
+    }
+
+    #[test]
+    fn try_evaluate_cache_keyed_up_to_alpha() {
+      // (λx. λy. <body>) a b
+      let select = |x, y, body| {
+        Term::apps([
+          Term::abs(x, Term::abs(y, Term::var(body))),
+          Term::var("a"),
+          Term::var("b"),
+        ])
+        .unwrap()
+      };
+      let mut interpreter = Interpreter::new().with_cache();
+      assert_eq!(interpreter.evaluate(&select("x", "y", "x")), Term::var("a"));
+      // same shape but a different binding structure, so it must miss
+      assert_eq!(interpreter.evaluate(&select("x", "y", "y")), Term::var("b"));
+
+      // α-equivalent to the first term, so it's served from the cache
+      let (result, stats) = interpreter.evaluate_with_stats(&select("p", "q", "p"));
+      assert_eq!(result, Term::var("a"));
+      assert_eq!(stats.beta_reductions, 0);
+    }
+
+    #[test]
+    fn try_evaluate_cache_keeps_free_variables() {
+      // free variables are part of the key, even ones named like canonical binders
+      let mut interpreter = Interpreter::new().with_cache();
+      let id_v0 = Term::app(Term::abs("x", Term::var("x")), Term::var("v0"));
+      let id_z = Term::app(Term::abs("x", Term::var("x")), Term::var("z"));
+      assert_eq!(interpreter.evaluate(&id_v0), Term::var("v0"));
+      assert_eq!(interpreter.evaluate(&id_z), Term::var("z"));
+    }
+
+    #[test]
+    fn step_outermost_first() {
+      // (λx. x) ((λy. y) z)
+      let inner = Term::app(Term::abs("y", Term::var("y")), Term::var("z"));
+      let term = Term::app(Term::abs("x", Term::var("x")), inner.clone());
+      let mut interpreter = Interpreter::new();
+      let once = interpreter.step(&term);
+      assert_eq!(once, Some(inner));
+      let twice = interpreter.step(&once.unwrap());
+      assert_eq!(twice, Some(Term::var("z")));
+      assert_eq!(interpreter.step(&twice.unwrap()), None);
+    }
+
+    #[test]
+    fn step_normal_form() {
+      let mut interpreter = Interpreter::new();
+      assert_eq!(interpreter.step(&Term::abs("x", Term::var("x"))), None);
+      assert_eq!(
+        interpreter.step(&Term::app(Term::var("x"), Term::var("y"))),
+        None
+      );
+    }
+
+    #[test]
+    fn step_under_binders() {
+      // λx. x ((λy. y) x) reduces inside both the body and the argument
+      let term = Term::abs(
+        "x",
+        Term::app(
+          Term::var("x"),
+          Term::app(Term::abs("y", Term::var("y")), Term::var("x")),
+        ),
+      );
+      let mut interpreter = Interpreter::new();
+      assert_eq!(
+        interpreter.step(&term),
+        Some(Term::abs("x", Term::app(Term::var("x"), Term::var("x"))))
+      );
+    }
+
+    #[test]
+    fn step_omega_reduces_to_itself() {
+      let mut interpreter = Interpreter::new();
+      assert_eq!(interpreter.step(&omega()), Some(omega()));
+    }
+
+    #[test]
+    fn evaluate_full_normalization() {
+      // λx. (λy. y) z
+      let term = Term::abs(
+        "x",
+        Term::app(Term::abs("y", Term::var("y")), Term::var("z")),
+      );
+      let mut interpreter = Interpreter::new();
+      assert_eq!(interpreter.evaluate(&term), term);
+      let mut interpreter = Interpreter::new().with_full_normalization();
+      assert_eq!(interpreter.evaluate(&term), Term::abs("x", Term::var("z")));
+    }
+
+    #[test]
+    fn evaluate_full_normalization_result_of_application() {
+      // (λf. λx. f x) (λy. y) only normalizes to λx. x under binders
+      let term = Term::app(
+        Term::abs(
+          "f",
+          Term::abs("x", Term::app(Term::var("f"), Term::var("x"))),
+        ),
+        Term::abs("y", Term::var("y")),
+      );
+      let mut interpreter = Interpreter::new();
+      assert_eq!(
+        interpreter.evaluate(&term),
+        Term::abs(
+          "x",
+          Term::app(Term::abs("y", Term::var("y")), Term::var("x"))
+        )
+      );
+      let mut interpreter = Interpreter::new().with_full_normalization();
+      assert_eq!(interpreter.evaluate(&term), Term::abs("x", Term::var("x")));
+    }
+
+    #[test]
+    fn try_evaluate_recursion_limit() {
+      // x x x ... x, nested far deeper than the limit along the left
+      let tower = (0..10_000).fold(Term::var("x"), |acc, _| Term::app(acc, Term::var("x")));
+      let mut interpreter = Interpreter::new().with_recursion_limit(1_000);
+      assert_eq!(
+        interpreter.try_evaluate(&tower),
+        Err(EvalError::RecursionLimit)
+      );
+    }
+
+    #[test]
+    fn try_evaluate_within_recursion_limit() {
+      let tower = (0..10).fold(Term::var("x"), |acc, _| Term::app(acc, Term::var("x")));
+      let mut interpreter = Interpreter::new().with_recursion_limit(1_000);
+      assert_eq!(interpreter.try_evaluate(&tower), Ok(tower));
+    }
+
+    #[test]
+    fn try_evaluate_recursion_limit_omega() {
+      // Ω's contractum is always in tail position so it never nests any deeper,
+      // only the step limit stops it
+      for interpreter in [Interpreter::new(), Interpreter::new().with_call_by_need()] {
+        let mut interpreter = interpreter.with_recursion_limit(5).with_max_steps(100_000);
+        assert_eq!(
+          interpreter.try_evaluate(&omega()),
+          Err(EvalError::StepLimitExceeded)
+        );
+      }
+      // whereas each contractum here is evaluated as the left of an application
+      let nesting = parse("(λx. x x y) (λx. x x y)");
+      for interpreter in [Interpreter::new(), Interpreter::new().with_call_by_need()] {
+        let mut interpreter = interpreter.with_recursion_limit(500);
+        assert_eq!(
+          interpreter.try_evaluate(&nesting),
+          Err(EvalError::RecursionLimit)
+        );
+      }
+    }
+
+    #[test]
+    fn try_evaluate_through_trait() {
+      fn run<'src>(
+        evaluator: &mut impl Evaluate<'src>,
+        term: &Term<'src>,
+      ) -> Result<Term<'src>, EvalError> {
+        evaluator.try_evaluate(term)
+      }
+      let mut interpreter = Interpreter::new().with_max_steps(10);
+      assert_eq!(
+        run(&mut interpreter, &omega()),
+        Err(EvalError::StepLimitExceeded)
+      );
+      assert_eq!(run(&mut interpreter, &parse("(λx. x) y")), Ok(parse("y")));
+
+      /// Evaluates nothing, relying on the default `try_evaluate`
+      struct Identity;
+
+      impl<'src> Evaluate<'src> for Identity {
+        fn evaluate(&mut self, term: &Term<'src>) -> Term<'src> {
+          term.clone()
+        }
+      }
+      assert_eq!(run(&mut Identity, &omega()), Ok(omega()));
+    }
+
+    #[test]
+    fn try_evaluate_timeout() {
+      for interpreter in [Interpreter::new(), Interpreter::new().with_call_by_need()] {
+        let mut interpreter = interpreter.with_timeout(Duration::from_millis(20));
+        assert_eq!(interpreter.try_evaluate(&omega()), Err(EvalError::Timeout));
+      }
+    }
+
+    #[test]
+    fn try_evaluate_within_timeout() {
+      let term = Term::apps([prelude::mult(), prelude::numeral(5), prelude::numeral(5)]).unwrap();
+      let mut interpreter = Interpreter::new()
+        .with_full_normalization()
+        .with_timeout(Duration::from_secs(60));
+      let result = interpreter.try_evaluate(&term).unwrap();
+      assert_eq!(result.as_church_numeral(), Some(25));
+    }
+
+    /// Mints `base0`, `base1`, ... instead of priming
+    struct NumberedNamer;
+
+    impl NameGen for NumberedNamer {
+      fn fresh(&mut self, base: &str, used: &HashSet<&str>) -> String {
+        (0..)
+          .map(|n| format!("{base}{n}"))
+          .find(|name| !used.contains(name.as_str()))
+          .unwrap()
+      }
+    }
+
+    #[test]
+    fn custom_name_gen() {
+      // (λy. λx. y) x must rename the inner binder to avoid capturing `x`
+      let term = Term::app(
+        Term::abs("y", Term::abs("x", Term::var("y"))),
+        Term::var("x"),
+      );
+      let primed = Interpreter::new().evaluate(&term);
+      assert_eq!(primed, Term::abs("x'", Term::var("x")));
+      let numbered = Interpreter::new()
+        .with_name_gen(NumberedNamer)
+        .evaluate(&term);
+      assert_eq!(numbered, Term::abs("x0", Term::var("x")));
+    }
+
+    #[test]
+    fn evaluate_in_env_unfolds_applied_names() {
+      let env = HashMap::from([("id", Term::abs("x", Term::var("x")))]);
+      let term = Term::app(Term::var("id"), Term::var("x"));
+      let result = Interpreter::new().evaluate_in_env(&term, &env);
+      assert_eq!(result, Ok(Term::var("x")));
+      // names that are never applied are left alone
+      let result = Interpreter::new().evaluate_in_env(&Term::var("id"), &env);
+      assert_eq!(result, Ok(Term::var("id")));
+    }
+
+    #[test]
+    fn evaluate_in_env_definitions_refer_to_each_other() {
+      let env = HashMap::from([
+        ("true", prelude::church_true()),
+        ("false", prelude::church_false()),
+        ("not", parse("λp. p false true")),
+      ]);
+      let term = parse("not true a b");
+      let result = Interpreter::new().evaluate_in_env(&term, &env);
+      assert_eq!(result, Ok(Term::var("b")));
+    }
+
+    #[test]
+    fn evaluate_in_env_recursive_definition() {
+      // only unfolded when applied, so this doesn't expand forever
+      let env = HashMap::from([("loop", parse("λx. loop x"))]);
+      let term = parse("λy. loop y");
+      let mut interpreter = Interpreter::new()
+        .with_full_normalization()
+        .with_max_steps(10);
+      assert_eq!(
+        interpreter.evaluate_in_env(&term, &env),
+        Err(EvalError::StepLimitExceeded)
+      );
+      let result = Interpreter::new().evaluate_in_env(&term, &env);
+      assert_eq!(result, Ok(term));
+    }
+
+    #[test]
+    fn evaluate_in_env_shadowed_by_binder() {
+      let env = HashMap::from([("id", Term::abs("x", Term::var("x")))]);
+      let term = parse("λid. id z");
+      let mut interpreter = Interpreter::new().with_full_normalization();
+      assert_eq!(interpreter.evaluate_in_env(&term, &env), Ok(term));
+    }
+
+    #[test]
+    fn numeral_arithmetic_matches_slow_path() {
+      let env = HashMap::from([("plus", prelude::plus()), ("mult", prelude::mult())]);
+      for src in [
+        "mult 30 30",
+        "plus 7 (mult 3 4)",
+        "mult 0 5",
+        "plus 2 (λf. f)",
+      ] {
+        let term = parse(src);
+        let slow = Interpreter::new()
+          .with_full_normalization()
+          .evaluate_in_env(&term, &env)
+          .unwrap();
+        let fast = Interpreter::new()
+          .with_full_normalization()
+          .with_numeral_arithmetic()
+          .evaluate_in_env(&term, &env)
+          .unwrap();
+        assert!(fast.alpha_eq(&slow), "{src}: {fast} vs {slow}");
+      }
+    }
+
+    #[test]
+    fn numeral_arithmetic_skips_reduction() {
+      let env = HashMap::from([("times", parse("λa. λb. λg. a (b g)"))]);
+      let term = parse("times 30 30");
+      let mut slow = Interpreter::new()
+        .with_full_normalization()
+        .with_max_steps(10);
+      assert_eq!(
+        slow.evaluate_in_env(&term, &env),
+        Err(EvalError::StepLimitExceeded)
+      );
+      let mut fast = Interpreter::new()
+        .with_full_normalization()
+        .with_numeral_arithmetic()
+        .with_max_steps(10);
+      let result = fast.evaluate_in_env(&term, &env).unwrap();
+      assert_eq!(result.as_church_numeral(), Some(900));
+    }
+
+    #[test]
+    fn numeral_arithmetic_guarded() {
+      let env = HashMap::from([("mult", prelude::mult())]);
+      // shadowed by a binder
+      let term = parse("λmult. mult 2 3");
+      let mut interpreter = Interpreter::new()
+        .with_full_normalization()
+        .with_numeral_arithmetic();
+      assert_eq!(interpreter.evaluate_in_env(&term, &env), Ok(term));
+      // without full normalization the slow path stops short of a numeral
+      let term = parse("mult 2 3");
+      let slow = Interpreter::new().evaluate_in_env(&term, &env);
+      let fast = Interpreter::new()
+        .with_numeral_arithmetic()
+        .evaluate_in_env(&term, &env);
+      assert_eq!(fast, slow);
+      // not something it recognizes
+      let env = HashMap::from([("mult", prelude::plus()), ("k", prelude::k())]);
+      let term = parse("k 2 3");
+      let result = interpreter.evaluate_in_env(&term, &env).unwrap();
+      assert_eq!(result.as_church_numeral(), Some(2));
+    }
+
+    #[test]
+    fn evaluation_order_changes_trace() {
+      let term = parse("((λx. x) a) ((λy. y) b)");
+      let traced = |order| {
+        let mut interpreter = Interpreter::new().with_order(order).with_trace();
+        let result = interpreter.evaluate(&term);
+        let trace: Vec<_> = interpreter.trace().iter().map(Term::to_string).collect();
+        (result, trace)
+      };
+      let (function_first, function_trace) = traced(EvalOrder::FunctionFirst);
+      let (argument_first, argument_trace) = traced(EvalOrder::ArgumentFirst);
+      assert_eq!(function_trace, ["(λx. x) a", "(λy. y) b"]);
+      assert_eq!(argument_trace, ["(λy. y) b", "(λx. x) a"]);
+      // same place in the end
+      assert_eq!(function_first, parse("a b"));
+      assert_eq!(function_first, argument_first);
+    }
+
+    #[test]
+    fn trace_off_by_default() {
+      let mut interpreter = Interpreter::new();
+      interpreter.evaluate(&parse("(λx. x) a"));
+      assert!(interpreter.trace().is_empty());
+    }
+
+    #[test]
+    fn observer_sees_reductions() {
+      use std::cell::RefCell;
+
+      let events = Rc::new(RefCell::new(Vec::new()));
+      let recorded = Rc::clone(&events);
+      let mut interpreter = Interpreter::new().with_observer(Box::new(move |event| {
+        let described = match event {
+          ReductionEvent::Beta { redex, contractum } => format!("β {redex} => {contractum}"),
+          ReductionEvent::Substitution {
+            term,
+            var,
+            value,
+            result,
+          } => format!("{term}[{var} := {value}] = {result}"),
+        };
+        recorded.borrow_mut().push(described);
+      }));
+      let result = interpreter.evaluate(&parse("(λx. λy. x) a b"));
+      assert_eq!(result, Term::var("a"));
+      assert_eq!(
+        *events.borrow(),
+        [
+          "λy. x[x := a] = λy. a",
+          "β (λx. λy. x) a => λy. a",
+          "a[y := b] = a",
+          "β (λy. a) b => a",
+        ]
+      );
+    }
+
+    #[test]
+    fn observer_sees_steps() {
+      use std::cell::Cell;
+
+      let betas = Rc::new(Cell::new(0));
+      let counted = Rc::clone(&betas);
+      let mut interpreter = Interpreter::new().with_observer(Box::new(move |event| {
+        if matches!(event, ReductionEvent::Beta { .. }) {
+          counted.set(counted.get() + 1);
+        }
+      }));
+      let term = parse("(λx. x) ((λy. y) z)");
+      let once = interpreter.step(&term).unwrap();
+      interpreter.step(&once).unwrap();
+      assert_eq!(betas.get(), 2);
+    }
+
+    #[test]
+    fn eta_reduce_contracts_everywhere() {
+      let mut interpreter = Interpreter::new();
+      assert_eq!(interpreter.eta_reduce(&parse("λx. f x")), parse("f"));
+      // nested, inside out
+      assert_eq!(interpreter.eta_reduce(&parse("λx. λy. f x y")), parse("f"));
+      assert_eq!(interpreter.eta_reduce(&parse("g (λy. h y)")), parse("g h"));
+      // `x` is needed by the function, so it has to stay
+      assert_eq!(interpreter.eta_reduce(&parse("λx. x x")), parse("λx. x x"));
+      assert_eq!(interpreter.eta_reduce(&parse("λx. x")), parse("λx. x"));
+    }
+
+    #[test]
+    fn eta_expand_fresh_binder() {
+      let mut interpreter = Interpreter::new();
+      assert_eq!(interpreter.eta_expand(&parse("f")), parse("λx. f x"));
+      // `x` is taken, so the binder has to be something else
+      assert_eq!(
+        interpreter.eta_expand(&parse("f x")),
+        Term::abs("x'", Term::app(parse("f x"), Term::var("x'")))
+      );
+      // only ever one level deep
+      let once = interpreter.eta_expand(&parse("f"));
+      assert_eq!(interpreter.eta_expand(&once), once);
+    }
+
+    #[test]
+    fn eta_round_trip() {
+      let mut interpreter = Interpreter::new();
+      for input in ["f", "f x", "x (λy. y)"] {
+        let term = parse(input);
+        let expanded = interpreter.eta_expand(&term);
+        assert_eq!(interpreter.eta_reduce(&expanded), term);
+      }
+      let reduced = interpreter.eta_reduce(&parse("λy. g y"));
+      assert_eq!(interpreter.eta_expand(&reduced), parse("λx. g x"));
+    }
+
+    #[test]
+    fn normalize_reaches_normal_form() {
+      let mut interpreter = Interpreter::new();
+      let outcome = interpreter.normalize(&parse("(λx. λy. x) a b"), 10);
+      assert_eq!(
+        outcome,
+        EvalOutcome {
+          term: parse("a"),
+          normal_form: true,
+          closed: false,
+          steps: 2,
+        }
+      );
+      // running out exactly as the normal form is reached still counts
+      let outcome = interpreter.normalize(&parse("(λx. λy. x) a b"), 2);
+      assert!(outcome.normal_form);
+      assert_eq!(outcome.steps, 2);
+    }
+
+    #[test]
+    fn normalize_reports_closed() {
+      let mut interpreter = Interpreter::new();
+      let stuck = interpreter.normalize(&parse("(λx. x) y"), 10);
+      assert_eq!(stuck.term, parse("y"));
+      assert!(stuck.normal_form && !stuck.closed);
+      let value = interpreter.normalize(&parse("(λx. x) (λy. y)"), 10);
+      assert_eq!(value.term, parse("λy. y"));
+      assert!(value.normal_form && value.closed);
+    }
+
+    #[test]
+    fn normalize_matches_stepping() {
+      let term = Term::apps([prelude::plus(), prelude::numeral(2), prelude::numeral(1)]).unwrap();
+      let mut interpreter = Interpreter::new();
+      let mut stepped = term.clone();
+      let mut steps = 0;
+      while let Some(next) = interpreter.step(&stepped) {
+        stepped = next;
+        steps += 1;
+      }
+      let outcome = interpreter.normalize(&term, 1000);
+      assert_eq!(outcome.term.as_church_numeral(), Some(3));
+      assert_eq!((outcome.term, outcome.steps), (stepped, steps));
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn evaluate_with_fuel_is_normalize() {
+      let mut interpreter = Interpreter::new();
+      let omega = parse("(λx. x x) (λx. x x)");
+      for term in [parse("(λx. λy. x) a b"), omega] {
+        assert_eq!(
+          interpreter.evaluate_with_fuel(&term, 5),
+          interpreter.normalize(&term, 5)
+        );
+      }
+    }
+
+    #[test]
+    fn normalize_truncates_divergence() {
+      let mut interpreter = Interpreter::new();
+      let omega = parse("(λx. x x) (λx. x x)");
+      let outcome = interpreter.normalize(&omega, 5);
+      assert!(!outcome.normal_form);
+      assert_eq!(outcome.steps, 5);
+      assert_eq!(outcome.term, omega);
+
+      let outcome = interpreter.normalize(&omega, 0);
+      assert_eq!((outcome.normal_form, outcome.steps), (false, 0));
+    }
+
+    #[test]
+    fn normal_form_hash_agrees() {
+      let mut interpreter = Interpreter::new();
+      let mut hash = |src| interpreter.normal_form_hash(&parse(src));
+      assert_eq!(hash("(λx. x) y"), hash("y"));
+      assert!(hash("y").is_some());
+      // same normal form up to α-equivalence
+      assert_eq!(hash("(λf. f) (λa. a)"), hash("λb. b"));
+      assert_ne!(hash("λx. λy. x"), hash("λx. λy. y"));
+      assert_ne!(hash("y"), hash("z"));
+      let two = Term::apps([prelude::plus(), prelude::numeral(1), prelude::numeral(1)]).unwrap();
+      assert_eq!(
+        interpreter.normal_form_hash(&two),
+        interpreter.normal_form_hash(&prelude::numeral(2))
+      );
+    }
+
+    #[test]
+    fn normal_form_hash_unfinished() {
+      let omega = parse("(λx. x x) (λx. x x)");
+      assert_eq!(Interpreter::new().normal_form_hash(&omega), None);
+      let slow = Term::apps([prelude::mult(), prelude::numeral(3), prelude::numeral(3)]).unwrap();
+      let mut interpreter = Interpreter::new().with_max_steps(2);
+      assert_eq!(interpreter.normal_form_hash(&slow), None);
+    }
+
+    #[test]
+    fn opaque_params_never_applied() {
+      let term = parse("(λf. f f) ((λx. x) y)");
+      let mut interpreter = Interpreter::new().with_opaque_params(["f"]);
+      assert_eq!(interpreter.evaluate(&term), parse("(λf. f f) y"));
+      assert_eq!(interpreter.step(&term), Some(parse("(λf. f f) y")));
+      let outcome = interpreter.normalize(&term, 10);
+      assert_eq!((outcome.term, outcome.steps), (parse("(λf. f f) y"), 1));
+      // other abstractions still apply, inside an opaque one too
+      let term = parse("(λf. (λx. x) f) ((λg. g) h)");
+      let mut interpreter = Interpreter::new()
+        .with_full_normalization()
+        .with_opaque_params(["f"]);
+      assert_eq!(interpreter.evaluate(&term), parse("(λf. f) h"));
+      assert_eq!(Interpreter::new().evaluate(&term), parse("h"));
+    }
+
+    #[test]
+    fn stuck_application_arguments_reduced() {
+      let term = parse("f ((λy. y) z)");
+      let expected = parse("f z");
+      for order in [EvalOrder::FunctionFirst, EvalOrder::ArgumentFirst] {
+        let result = Interpreter::new().with_order(order).evaluate(&term);
+        assert_eq!(result, expected, "{order:?}");
+      }
+      let lazy = Interpreter::new().with_call_by_need().evaluate(&term);
+      assert_eq!(lazy, expected);
+      let outcome = Interpreter::new().normalize(&term, 10);
+      assert_eq!((outcome.term, outcome.steps), (expected, 1));
+      // arguments that are already normal are left alone
+      let term = parse("f (λx. x) ((λy. y) z)");
+      assert_eq!(Interpreter::new().evaluate(&term), parse("f (λx. x) z"));
+    }
+
+    #[test]
+    fn stuck_application_arguments_under_budget() {
+      let term = parse("f ((λy. y) z) ((λw. w) v)");
+      let mut interpreter = Interpreter::new();
+      // normal order reaches the leftmost argument first
+      let outcome = interpreter.normalize(&term, 1);
+      assert_eq!(outcome.term, parse("f z ((λw. w) v)"));
+      assert!(!outcome.normal_form);
+      let outcome = interpreter.normalize(&term, 2);
+      assert_eq!(outcome.term, parse("f z v"));
+      assert!(outcome.normal_form);
+      // a step limit gives up instead of keeping what was reached
+      let mut interpreter = Interpreter::new().with_max_steps(1);
+      assert_eq!(
+        interpreter.try_evaluate(&term),
+        Err(EvalError::StepLimitExceeded)
+      );
+    }
+
+    #[test]
+    fn reductions_report_redex_path() {
+      let term = parse("(λx. x) ((λy. y) z)");
+      let mut interpreter = Interpreter::new();
+      let steps: Vec<_> = interpreter.reductions(&term).collect();
+      assert_eq!(steps.len(), 2);
+      // normal order contracts the outer application first
+      assert_eq!(steps[0].before, term);
+      assert_eq!(steps[0].redex_path, []);
+      assert_eq!(steps[0].after, parse("(λy. y) z"));
+      assert_eq!(steps[1].after, parse("z"));
+    }
+
+    #[test]
+    fn reductions_reach_into_subterms() {
+      let term = parse("λa. a b ((λx. x) c) ((λy. y) d)");
+      let mut interpreter = Interpreter::new();
+      let paths: Vec<_> = interpreter
+        .reductions(&term)
+        .map(|step| {
+          let redex = step.before.at(&step.redex_path).unwrap();
+          assert!(redex.is_redex());
+          step.redex_path
+        })
+        .collect();
+      assert_eq!(
+        paths,
+        [
+          vec![Step::Body, Step::Lhs, Step::Rhs],
+          vec![Step::Body, Step::Rhs]
+        ]
+      );
+      assert_eq!(interpreter.reductions(&parse("x y")).next(), None);
+    }
+
+    #[test]
+    fn restored_names_after_evaluation() {
+      // substituting `y` for `x` forces the binder to be renamed, but the `y` that
+      // clashed has gone by the time evaluation finishes
+      let term = parse("(λx. λy. x (λk. λm. m)) (λf. f y)");
+      let primed = Interpreter::new().with_full_normalization().evaluate(&term);
+      assert_eq!(primed, Term::abs("y'", parse("λm. m")));
+      let mut interpreter = Interpreter::new()
+        .with_full_normalization()
+        .with_restored_names();
+      assert_eq!(interpreter.evaluate(&term), parse("λy. λm. m"));
+
+      // where the clash remains the prime has to stay
+      let clash = parse("(λx. λy. x) y");
+      assert_eq!(
+        interpreter.evaluate(&clash),
+        Term::abs("y'", Term::var("y"))
+      );
+    }
+
+    #[test]
+    fn call_by_need_shares_arguments() {
+      let term = parse("(λx. x x) ((λy. y) z)");
+      let mut lazy = Interpreter::new().with_call_by_need();
+      let (result, stats) = lazy.evaluate_with_stats(&term);
+      assert_eq!(result, parse("z z"));
+      // one for the outer redex, one for the argument, however often it's used
+      assert_eq!(stats.beta_reductions, 2);
+
+      // by name (`step` in normal order) the argument is reduced once per use
+      let mut by_name = Interpreter::new();
+      let outcome = by_name.normalize(&term, 10);
+      assert_eq!(outcome.term, parse("z z"));
+      assert_eq!(outcome.steps, 3);
+    }
+
+    #[test]
+    fn call_by_need_skips_unused_arguments() {
+      let term = parse("(λx. λy. y) ((λy. y) z)");
+      let (result, stats) = Interpreter::new()
+        .with_call_by_need()
+        .evaluate_with_stats(&term);
+      assert_eq!(result, parse("λy. y"));
+      assert_eq!(stats.beta_reductions, 1);
+      // by value, the argument is evaluated regardless
+      let (_, stats) = Interpreter::new().evaluate_with_stats(&term);
+      assert_eq!(stats.beta_reductions, 2);
+
+      // even one that would never finish
+      let term = parse("(λx. λy. y) ((λx. x x) (λx. x x))");
+      let mut lazy = Interpreter::new().with_call_by_need();
+      assert_eq!(lazy.evaluate(&term), parse("λy. y"));
+    }
+
+    #[test]
+    fn call_by_need_agrees_with_substitution() {
+      let mut lazy = Interpreter::new()
+        .with_call_by_need()
+        .with_full_normalization();
+      let mut eager = Interpreter::new().with_full_normalization();
+      let cases = [
+        Term::apps([prelude::plus(), prelude::numeral(3), prelude::numeral(4)]).unwrap(),
+        Term::apps([prelude::mult(), prelude::numeral(3), prelude::numeral(2)]).unwrap(),
+        parse("(λx. λy. x) y"),
+        parse("(λf. λx. f (f x)) (λy. a y)"),
+      ];
+      for term in cases {
+        let expected = eager.evaluate(&term);
+        assert!(lazy.evaluate(&term).alpha_eq(&expected), "{term}");
+      }
+      // `w` is free in what `x` stands for, but bound in the closure itself
+      let mut lazy = Interpreter::new().with_call_by_need();
+      assert_eq!(
+        lazy.evaluate(&parse("(λx. λw. λy. x w) w r")),
+        parse("λy. w r")
+      );
+      // unevaluated arguments still get substituted into what's left
+      let mut lazy = Interpreter::new().with_call_by_need();
+      assert_eq!(
+        lazy.evaluate(&parse("(λx. λy. x) ((λw. w) a)")),
+        parse("λy. (λw. w) a")
+      );
+    }
+
+    #[test]
+    fn call_by_need_limits_and_env() {
+      let omega = parse("(λx. x x) (λx. x x)");
+      let mut lazy = Interpreter::new().with_call_by_need().with_max_steps(50);
+      assert_eq!(lazy.try_evaluate(&omega), Err(EvalError::StepLimitExceeded));
+
+      let env = HashMap::from([("id", parse("λx. x"))]);
+      let mut lazy = Interpreter::new().with_call_by_need();
+      assert_eq!(
+        lazy.evaluate_in_env(&parse("id (id a)"), &env),
+        Ok(parse("a"))
+      );
+    }
+
+    #[test]
+    fn try_evaluate_term_size_limit() {
+      // 2^4 as Church numerals, whose normal form alone is over 30 nodes
+      let pow = parse("(λm. λn. n m) (λf. λx. f (f x)) (λf. λx. f (f (f (f x))))");
+      let mut interpreter = Interpreter::new()
+        .with_full_normalization()
+        .with_max_term_size(30);
+      assert_eq!(interpreter.try_evaluate(&pow), Err(EvalError::TermTooLarge));
+
+      let small = parse("(λx. x) (λy. y)");
+      assert_eq!(interpreter.try_evaluate(&small), Ok(parse("λy. y")));
+      // without a limit the same evaluation is fine
+      let mut unlimited = Interpreter::new().with_full_normalization();
+      assert_eq!(unlimited.evaluate(&pow).as_church_numeral(), Some(16));
+    }
+
+    #[test]
+    fn y_combinator_recursion_hits_limits() {
+      // a function that only ever calls itself again
+      let forever = Term::app(
+        Term::app(prelude::y(), parse("λf. λn. f n")),
+        Term::var("a"),
+      );
+      let mut interpreter = Interpreter::new().with_max_steps(200);
+      assert_eq!(
+        interpreter.try_evaluate(&forever),
+        Err(EvalError::StepLimitExceeded)
+      );
+      // the argument isn't even needed for the self-application to go on
+      let bare = Term::app(prelude::y(), parse("λf. f"));
+      assert_eq!(
+        interpreter.try_evaluate(&bare),
+        Err(EvalError::StepLimitExceeded)
+      );
+      let mut interpreter = Interpreter::new().with_call_by_need().with_max_steps(200);
+      assert_eq!(
+        interpreter.try_evaluate(&forever),
+        Err(EvalError::StepLimitExceeded)
+      );
+      // each step nests a little deeper when walking the tree, so a big step budget
+      // alone could run out of stack first, but not alongside a recursion limit
+      let mut interpreter = Interpreter::new()
+        .with_max_steps(100_000)
+        .with_recursion_limit(500);
+      assert_eq!(
+        interpreter.try_evaluate(&forever),
+        Err(EvalError::RecursionLimit)
+      );
+      // whereas call-by-need goes on with each body in place, never nesting
+      let mut interpreter = Interpreter::new()
+        .with_call_by_need()
+        .with_max_steps(100_000)
+        .with_recursion_limit(500);
+      assert_eq!(
+        interpreter.try_evaluate(&forever),
+        Err(EvalError::StepLimitExceeded)
+      );
+    }
+
+    #[test]
+    fn y_combinator_recursion_can_stop() {
+      // ignoring the recursive call ends the recursion straight away
+      let term = Term::apps([prelude::y(), parse("λf. λn. n"), Term::var("a")]).unwrap();
+      let mut interpreter = Interpreter::new().with_call_by_need().with_max_steps(1000);
+      assert_eq!(interpreter.try_evaluate(&term), Ok(Term::var("a")));
+    }
   }
 }
@@ -2,15 +2,25 @@
 //! Handles conversion from `&'src str` to `Iterator<Item=Token<'src>>`
 //!
 
+use std::collections::VecDeque;
+use std::io::{self, BufRead};
 use std::iter::Peekable;
-use std::str::CharIndices;
+use std::str::{self, CharIndices, Utf8Error};
 
-use crate::token::Token;
+use crate::token::{OwnedToken, Span, Token};
 
 /// An iterator over lambda calculus tokens
 pub struct Lexer<'src> {
   input: &'src str,
   chars: Peekable<CharIndices<'src>>,
+  /// Token already lexed by `peek_token`, but not yet handed out
+  peeked: Option<(Token<'src>, Span)>,
+  /// Whether to keep whitespace and comments as `Token::Trivia`
+  trivia: bool,
+  /// Whether the input is all ASCII, in which case it's scanned a byte at a time
+  /// from `pos` and `chars` goes unused
+  ascii: bool,
+  pos: usize,
 }
 
 impl<'src> Lexer<'src> {
@@ -18,11 +28,101 @@ impl<'src> Lexer<'src> {
     Self {
       input,
       chars: input.char_indices().peekable(),
+      peeked: None,
+      trivia: false,
+      ascii: input.is_ascii(),
+      pos: 0,
     }
   }
 
+  /// Lex raw bytes, e.g. straight off a socket, borrowing them as a `&str` once
+  /// they're known to be valid UTF-8
+  pub fn from_bytes(input: &'src [u8]) -> Result<Self, Utf8Error> {
+    str::from_utf8(input).map(Self::new)
+  }
+
+  /// Keep whitespace and comments, each run of them as a single `Token::Trivia`,
+  /// rather than discarding them, e.g. for a formatter to put back
+  /// - `Parser` skips trivia tokens, and positions in its errors don't count
+  ///   them, as `ParseError::render` expects
+  pub fn with_trivia(mut self) -> Self {
+    self.trivia = true;
+    self
+  }
+
+  /// Byte index in the input that lexing will resume from
+  /// - whitespace preceding the next token hasn't been skipped yet, so this may
+  ///   point just before it, unless it's been peeked
+  pub fn offset(&self) -> usize {
+    if let Some((_, span)) = &self.peeked {
+      return span.start;
+    }
+    if self.ascii {
+      return self.pos;
+    }
+    // peeking needs mutable access, but cloning the cursor is cheap
+    self
+      .chars
+      .clone()
+      .peek()
+      .map(|(idx, _)| *idx)
+      .unwrap_or(self.input.len())
+  }
+
+  /// Report where in the input each token came from, alongside the token
+  pub fn spanned(self) -> Spanned<'src> {
+    Spanned { lexer: self }
+  }
+
+  /// Lex the rest of the input up front
+  pub fn tokenize(self) -> Vec<Token<'src>> {
+    self.collect()
+  }
+
+  /// Lex the rest of the input up front, keeping where each token came from
+  pub fn tokenize_spanned(self) -> Vec<(Token<'src>, Span)> {
+    self.spanned().collect()
+  }
+
+  /// Look at the next token without consuming it, so the next call to `next`
+  /// gives the same one
+  pub fn peek_token(&mut self) -> Option<&Token<'src>> {
+    if self.peeked.is_none() {
+      self.peeked = self.next_spanned();
+    }
+    self.peeked.as_ref().map(|(token, _)| token)
+  }
+
+  fn next_spanned(&mut self) -> Option<(Token<'src>, Span)> {
+    if let Some(peeked) = self.peeked.take() {
+      return Some(peeked);
+    }
+    let start = self.offset();
+    self.skip_trivia();
+    let end = self.offset();
+    if self.trivia && end > start {
+      return Some((Token::Trivia(&self.input[start..end]), Span { start, end }));
+    }
+    let start = end;
+    let token = self.read_token()?;
+    Some((
+      token,
+      Span {
+        start,
+        end: self.offset(),
+      },
+    ))
+  }
+
   fn next_token(&mut self) -> Option<Token<'src>> {
-    self.skip_whitespace();
+    self.next_spanned().map(|(token, _)| token)
+  }
+
+  fn read_token(&mut self) -> Option<Token<'src>> {
+    if self.ascii {
+      return self.read_token_ascii();
+    }
+    self.skip_trivia();
     match self.peek_char() {
       Some('(') => {
         // consume '('
@@ -33,25 +133,83 @@ impl<'src> Lexer<'src> {
         self.chars.next();
         Some(Token::RParen)
       }
-      Some('λ') | Some('\\') => {
+      Some(glyph @ ('λ' | '\\')) => {
         self.chars.next();
-        Some(Token::Lambda)
+        Some(Token::Lambda(glyph))
       }
       Some('.') => {
         self.chars.next();
         Some(Token::Dot)
       }
-      Some(c) if c.is_ascii_lowercase() => self.read_binding(),
+      Some('=') => {
+        self.chars.next();
+        Some(Token::Equals)
+      }
+      Some(';') => {
+        self.chars.next();
+        Some(Token::Semicolon)
+      }
+      Some(',') => {
+        self.chars.next();
+        Some(Token::Comma)
+      }
+      Some('-') => {
+        self.chars.next();
+        // only valid as the start of '->'
+        match self.peek_char() {
+          Some('>') => {
+            self.chars.next();
+            Some(Token::Arrow)
+          }
+          _ => Some(Token::Invalid('-')),
+        }
+      }
+      Some(c) if is_binding_start(c) => self.read_binding(),
+      Some('`') => self.read_escaped(),
+      Some(c) if c.is_ascii_digit() => self.read_number(),
       None => None,
-      // TODO:
-      // introduce `Invalid` token...
-      _ => panic!("Unexpected character: {:?}", self.chars.peek()),
+      // leave it to the parser to reject
+      Some(c) => {
+        self.chars.next();
+        Some(Token::Invalid(c))
+      }
+    }
+  }
+
+  /// Skip anything that doesn't affect the meaning of the input, namely
+  /// whitespace and `--` line comments
+  fn skip_trivia(&mut self) {
+    if self.ascii {
+      return self.skip_trivia_ascii();
+    }
+    loop {
+      self.skip_whitespace();
+      if !self.at_comment() {
+        break;
+      }
+      // discard the rest of the line, the newline goes with the whitespace
+      while let Some(ch) = self.peek_char() {
+        if ch == '\n' {
+          break;
+        }
+        self.chars.next();
+      }
     }
   }
 
+  fn at_comment(&self) -> bool {
+    let mut ahead = self.chars.clone();
+    matches!(
+      (ahead.next(), ahead.next()),
+      (Some((_, '-')), Some((_, '-')))
+    )
+  }
+
+  /// Only plain spaces, tabs, and line breaks count as whitespace, anything more
+  /// exotic (form feeds, NULs, Unicode spaces) is more likely a mistake
   fn skip_whitespace(&mut self) {
     while let Some(ch) = self.peek_char() {
-      if ch.is_whitespace() {
+      if matches!(ch, ' ' | '\t' | '\n' | '\r') {
         self.chars.next();
       } else {
         break;
@@ -66,7 +224,7 @@ impl<'src> Lexer<'src> {
     let start = self.chars.peek().map(|(idx, _)| *idx)?;
 
     while let Some(ch) = self.peek_char() {
-      if ch.is_ascii_alphanumeric() {
+      if is_binding_char(ch) {
         self.chars.next();
       } else {
         break;
@@ -80,13 +238,135 @@ impl<'src> Lexer<'src> {
       .map(|(idx, _)| *idx)
       .unwrap_or_else(|| self.input.len());
 
-    // spit out the middle
-    Some(Token::Binding(&self.input[start..end]))
+    // spit out the middle, unless it's reserved
+    match &self.input[start..end] {
+      "let" => Some(Token::Let),
+      "in" => Some(Token::In),
+      name => Some(Token::Binding(name)),
+    }
+  }
+
+  /// Read an identifier quoted in backticks, like `` `let` ``, which is always a
+  /// binding even when it spells a keyword
+  /// - the quoted text must still be an identifier, otherwise the '`' is
+  ///   `Token::Invalid` and lexing carries on just past it
+  fn read_escaped(&mut self) -> Option<Token<'src>> {
+    let (tick, _) = self.chars.next()?;
+    let start = tick + 1;
+    let mut rest = self.input[start..].char_indices();
+    let valid = rest.next().is_some_and(|(_, c)| is_binding_start(c));
+    let end = rest
+      .find(|(_, c)| !is_binding_char(*c))
+      .filter(|(_, c)| *c == '`')
+      .map(|(idx, _)| start + idx);
+    match end.filter(|_| valid) {
+      Some(end) => {
+        // skip past the name and its closing backtick
+        while self.offset() <= end {
+          self.chars.next();
+        }
+        Some(Token::Binding(&self.input[start..end]))
+      }
+      None => Some(Token::Invalid('`')),
+    }
+  }
+
+  /// Read a run of ASCII digits as a number
+  /// - only ever an integer, so `3.x` is `3` followed by `.`
+  /// - digits run straight into letters, so `3x` is `3` then `x`
+  /// - a number too big for `u64` is consumed whole, as a `Token::Invalid` of its
+  ///   first digit
+  fn read_number(&mut self) -> Option<Token<'src>> {
+    let start = self.offset();
+    while self.peek_char().is_some_and(|ch| ch.is_ascii_digit()) {
+      self.chars.next();
+    }
+    let digits = &self.input[start..self.offset()];
+    match digits.parse() {
+      Ok(n) => Some(Token::Number(n)),
+      Err(_) => digits.chars().next().map(Token::Invalid),
+    }
   }
 
   fn peek_char(&mut self) -> Option<char> {
     self.chars.peek().map(|(_, c)| *c)
   }
+
+  /// `read_token` for ASCII input, indexing straight into its bytes rather than
+  /// going through `chars`, which is quite a bit faster
+  /// - must give exactly the tokens `read_token` would
+  fn read_token_ascii(&mut self) -> Option<Token<'src>> {
+    self.skip_trivia_ascii();
+    let bytes = self.input.as_bytes();
+    let start = self.pos;
+    let byte = *bytes.get(start)?;
+    // how far a run of bytes matching `pred` extends from `from`
+    let run = |from: usize, pred: fn(&u8) -> bool| {
+      from + bytes[from..].iter().take_while(|b| pred(b)).count()
+    };
+    self.pos += 1;
+    let token = match byte {
+      b'(' => Token::LParen,
+      b')' => Token::RParen,
+      b'\\' => Token::Lambda('\\'),
+      b'.' => Token::Dot,
+      b'=' => Token::Equals,
+      b';' => Token::Semicolon,
+      b',' => Token::Comma,
+      b'-' if bytes.get(self.pos) == Some(&b'>') => {
+        self.pos += 1;
+        Token::Arrow
+      }
+      b'a'..=b'z' => {
+        self.pos = run(self.pos, is_ascii_binding_byte);
+        match &self.input[start..self.pos] {
+          "let" => Token::Let,
+          "in" => Token::In,
+          name => Token::Binding(name),
+        }
+      }
+      b'`' => {
+        let end = run(self.pos, is_ascii_binding_byte);
+        let valid = bytes.get(self.pos).is_some_and(u8::is_ascii_lowercase);
+        if valid && bytes.get(end) == Some(&b'`') {
+          let name = &self.input[self.pos..end];
+          self.pos = end + 1;
+          Token::Binding(name)
+        } else {
+          Token::Invalid('`')
+        }
+      }
+      b'0'..=b'9' => {
+        self.pos = run(self.pos, u8::is_ascii_digit);
+        match self.input[start..self.pos].parse() {
+          Ok(n) => Token::Number(n),
+          Err(_) => Token::Invalid(char::from(byte)),
+        }
+      }
+      // a lone '-' included, leave it to the parser to reject
+      _ => Token::Invalid(char::from(byte)),
+    };
+    Some(token)
+  }
+
+  /// `skip_trivia` for ASCII input, see `read_token_ascii`
+  fn skip_trivia_ascii(&mut self) {
+    let bytes = self.input.as_bytes();
+    loop {
+      while bytes
+        .get(self.pos)
+        .is_some_and(|b| matches!(b, b' ' | b'\t' | b'\n' | b'\r'))
+      {
+        self.pos += 1;
+      }
+      if !bytes[self.pos..].starts_with(b"--") {
+        break;
+      }
+      while bytes.get(self.pos).is_some_and(|b| *b != b'\n') {
+        self.pos += 1;
+      }
+    }
+  }
 }
 
 impl<'a> Iterator for Lexer<'a> {
@@ -97,6 +377,103 @@ impl<'a> Iterator for Lexer<'a> {
   }
 }
 
+/// An iterator over lambda calculus tokens and the spans they were read from
+pub struct Spanned<'src> {
+  lexer: Lexer<'src>,
+}
+
+impl<'src> Iterator for Spanned<'src> {
+  type Item = (Token<'src>, Span);
+
+  fn next(&mut self) -> Option<Self::Item> {
+    self.lexer.next_spanned()
+  }
+}
+
+/// Identifiers start with any lowercase letter, ASCII or not
+/// - except 'λ', which always introduces an abstraction
+fn is_binding_start(c: char) -> bool {
+  c.is_lowercase() && c != 'λ'
+}
+
+/// ...and continue with any letter or digit, again excluding 'λ', or a prime
+/// - digits include the subscripts '₀' to '₉', so `x₁` is one identifier, though
+///   being digits they can't start one
+/// - primes can't start one either, but can follow on, as in `x'` or `f''`
+fn is_binding_char(c: char) -> bool {
+  (c.is_alphanumeric() && c != 'λ') || c == '\''
+}
+
+/// `is_binding_char` for a byte of ASCII input
+fn is_ascii_binding_byte(b: &u8) -> bool {
+  b.is_ascii_alphanumeric() || *b == b'\''
+}
+
+/// An iterator over owned tokens read incrementally from some `BufRead`, so the
+/// whole input never needs to be in memory at once
+/// - input is lexed a line at a time, which works since no token (or comment)
+///   spans more than one line
+pub struct OwnedLexer<R> {
+  reader: R,
+  line: String,
+  pending: VecDeque<OwnedToken>,
+}
+
+impl<R: BufRead> OwnedLexer<R> {
+  pub fn new(reader: R) -> Self {
+    Self {
+      reader,
+      line: String::new(),
+      pending: VecDeque::new(),
+    }
+  }
+}
+
+impl<R: BufRead> Iterator for OwnedLexer<R> {
+  type Item = io::Result<OwnedToken>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    // keep pulling lines until one has something in it
+    while self.pending.is_empty() {
+      self.line.clear();
+      match self.reader.read_line(&mut self.line) {
+        Ok(0) => return None,
+        Ok(_) => self
+          .pending
+          .extend(Lexer::new(&self.line).map(OwnedToken::from)),
+        Err(e) => return Some(Err(e)),
+      }
+    }
+    self.pending.pop_front().map(Ok)
+  }
+}
+
+/// Rewrites each token of an underlying stream before it reaches the parser, e.g.
+/// to rename bindings, `Parser::new(TokenMap::new(lexer, f))`
+/// - to drop tokens instead, `Iterator::filter` works just as well
+pub struct TokenMap<I, F> {
+  tokens: I,
+  f: F,
+}
+
+impl<I, F> TokenMap<I, F> {
+  pub fn new(tokens: I, f: F) -> Self {
+    Self { tokens, f }
+  }
+}
+
+impl<'src, I, F> Iterator for TokenMap<I, F>
+where
+  I: Iterator<Item = Token<'src>>,
+  F: FnMut(Token<'src>) -> Token<'src>,
+{
+  type Item = Token<'src>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    self.tokens.next().map(&mut self.f)
+  }
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -107,7 +484,7 @@ mod tests {
     #[test]
     fn next_token() {
       let mut lexer = Lexer::new("λx.x");
-      assert_eq!(lexer.next_token(), Some(Token::Lambda));
+      assert_eq!(lexer.next_token(), Some(Token::Lambda('λ')));
       assert_eq!(lexer.next_token(), Some(Token::Binding("x")));
       assert_eq!(lexer.next_token(), Some(Token::Dot));
       assert_eq!(lexer.next_token(), Some(Token::Binding("x")));
@@ -118,7 +495,7 @@ mod tests {
     fn next_token_parens() {
       let mut lexer = Lexer::new("(λx.(x x))");
       assert_eq!(lexer.next_token(), Some(Token::LParen));
-      assert_eq!(lexer.next_token(), Some(Token::Lambda));
+      assert_eq!(lexer.next_token(), Some(Token::Lambda('λ')));
       assert_eq!(lexer.next_token(), Some(Token::Binding("x")));
       assert_eq!(lexer.next_token(), Some(Token::Dot));
       assert_eq!(lexer.next_token(), Some(Token::LParen));
@@ -132,11 +509,583 @@ mod tests {
     #[test]
     fn next_token_whitespace() {
       let mut lexer = Lexer::new("   λ  x   . x  ");
-      assert_eq!(lexer.next_token(), Some(Token::Lambda));
+      assert_eq!(lexer.next_token(), Some(Token::Lambda('λ')));
       assert_eq!(lexer.next_token(), Some(Token::Binding("x")));
       assert_eq!(lexer.next_token(), Some(Token::Dot));
       assert_eq!(lexer.next_token(), Some(Token::Binding("x")));
       assert_eq!(lexer.next_token(), None);
     }
+
+    #[test]
+    fn next_token_arrow() {
+      let mut lexer = Lexer::new("\\x -> x");
+      assert_eq!(lexer.next_token(), Some(Token::Lambda('\\')));
+      assert_eq!(lexer.next_token(), Some(Token::Binding("x")));
+      assert_eq!(lexer.next_token(), Some(Token::Arrow));
+      assert_eq!(lexer.next_token(), Some(Token::Binding("x")));
+      assert_eq!(lexer.next_token(), None);
+    }
+
+    #[test]
+    fn next_token_lone_dash() {
+      let tokens: Vec<_> = Lexer::new("\\x - x").collect();
+      assert_eq!(
+        tokens,
+        vec![
+          Token::Lambda('\\'),
+          Token::Binding("x"),
+          Token::Invalid('-'),
+          Token::Binding("x"),
+        ]
+      );
+    }
+
+    #[test]
+    fn next_token_invalid() {
+      let tokens: Vec<_> = Lexer::new("x ? Y").collect();
+      assert_eq!(
+        tokens,
+        vec![
+          Token::Binding("x"),
+          Token::Invalid('?'),
+          Token::Invalid('Y')
+        ]
+      );
+    }
+
+    #[test]
+    fn next_token_control_characters() {
+      let tokens: Vec<_> = Lexer::new("x\0y\u{c}z").collect();
+      assert_eq!(
+        tokens,
+        vec![
+          Token::Binding("x"),
+          Token::Invalid('\0'),
+          Token::Binding("y"),
+          Token::Invalid('\u{c}'),
+          Token::Binding("z"),
+        ]
+      );
+      // unlike plain tabs and line breaks
+      let tokens: Vec<_> = Lexer::new("x\ty\r\nz").collect();
+      assert_eq!(
+        tokens,
+        vec![
+          Token::Binding("x"),
+          Token::Binding("y"),
+          Token::Binding("z")
+        ]
+      );
+    }
+
+    #[test]
+    fn next_token_lambda_glyph() {
+      assert_eq!(Lexer::new("\\x.x").next(), Some(Token::Lambda('\\')));
+      assert_eq!(Lexer::new("λx.x").next(), Some(Token::Lambda('λ')));
+    }
+
+    #[test]
+    fn next_token_definition() {
+      let mut lexer = Lexer::new("id = λx. x;");
+      assert_eq!(lexer.next_token(), Some(Token::Binding("id")));
+      assert_eq!(lexer.next_token(), Some(Token::Equals));
+      assert_eq!(lexer.next_token(), Some(Token::Lambda('λ')));
+      assert_eq!(lexer.next_token(), Some(Token::Binding("x")));
+      assert_eq!(lexer.next_token(), Some(Token::Dot));
+      assert_eq!(lexer.next_token(), Some(Token::Binding("x")));
+      assert_eq!(lexer.next_token(), Some(Token::Semicolon));
+      assert_eq!(lexer.next_token(), None);
+    }
+
+    #[test]
+    fn next_token_comments() {
+      let commented: Vec<_> = Lexer::new("λx. x -- identity\n y").collect();
+      let plain: Vec<_> = Lexer::new("λx. x y").collect();
+      assert_eq!(commented, plain);
+    }
+
+    #[test]
+    fn next_token_comment_only() {
+      let mut lexer = Lexer::new("-- nothing here\n  -- or here");
+      assert_eq!(lexer.next_token(), None);
+    }
+
+    #[test]
+    fn next_token_comment_adjacent() {
+      // comments end identifiers, and don't swallow the next line
+      let mut lexer = Lexer::new("x--y\n->z");
+      assert_eq!(lexer.next_token(), Some(Token::Binding("x")));
+      assert_eq!(lexer.next_token(), Some(Token::Arrow));
+      assert_eq!(lexer.next_token(), Some(Token::Binding("z")));
+      assert_eq!(lexer.next_token(), None);
+    }
+
+    #[test]
+    fn next_token_unicode_bindings() {
+      let tokens: Vec<_> = Lexer::new("α δx x₁").collect();
+      assert_eq!(
+        tokens,
+        vec![
+          Token::Binding("α"),
+          Token::Binding("δx"),
+          Token::Binding("x₁"),
+        ]
+      );
+    }
+
+    #[test]
+    fn next_token_numbers() {
+      let tokens: Vec<_> = Lexer::new("3.x").collect();
+      assert_eq!(
+        tokens,
+        vec![Token::Number(3), Token::Dot, Token::Binding("x")]
+      );
+      let tokens: Vec<_> = Lexer::new("x2.y").collect();
+      assert_eq!(
+        tokens,
+        vec![Token::Binding("x2"), Token::Dot, Token::Binding("y")]
+      );
+      // no floats, and digits don't glue onto what follows
+      let tokens: Vec<_> = Lexer::new("1.5 10x 007").collect();
+      assert_eq!(
+        tokens,
+        vec![
+          Token::Number(1),
+          Token::Dot,
+          Token::Number(5),
+          Token::Number(10),
+          Token::Binding("x"),
+          Token::Number(7),
+        ]
+      );
+      let tokens: Vec<_> = Lexer::new("99999999999999999999 y").collect();
+      assert_eq!(tokens, vec![Token::Invalid('9'), Token::Binding("y")]);
+    }
+
+    #[test]
+    fn next_token_keywords() {
+      let tokens: Vec<_> = Lexer::new("let x = y in x").collect();
+      assert_eq!(
+        tokens,
+        vec![
+          Token::Let,
+          Token::Binding("x"),
+          Token::Equals,
+          Token::Binding("y"),
+          Token::In,
+          Token::Binding("x"),
+        ]
+      );
+      // only whole words are keywords
+      let tokens: Vec<_> = Lexer::new("lets inn in2").collect();
+      assert_eq!(
+        tokens,
+        vec![
+          Token::Binding("lets"),
+          Token::Binding("inn"),
+          Token::Binding("in2"),
+        ]
+      );
+    }
+
+    #[test]
+    fn next_token_escaped_bindings() {
+      let tokens: Vec<_> = Lexer::new("`let` `in`.`x₁`").collect();
+      assert_eq!(
+        tokens,
+        vec![
+          Token::Binding("let"),
+          Token::Binding("in"),
+          Token::Dot,
+          Token::Binding("x₁"),
+        ]
+      );
+      // unterminated, empty, or not an identifier inside
+      let tokens: Vec<_> = Lexer::new("`let").collect();
+      assert_eq!(tokens, vec![Token::Invalid('`'), Token::Let]);
+      let tokens: Vec<_> = Lexer::new("``").collect();
+      assert_eq!(tokens, vec![Token::Invalid('`'), Token::Invalid('`')]);
+      let tokens: Vec<_> = Lexer::new("`a b`").collect();
+      assert_eq!(
+        tokens,
+        vec![
+          Token::Invalid('`'),
+          Token::Binding("a"),
+          Token::Binding("b"),
+          Token::Invalid('`'),
+        ]
+      );
+    }
+
+    #[test]
+    fn next_token_subscripts() {
+      let tokens: Vec<_> = Lexer::new("x₁ x₂ y₁₀").collect();
+      assert_eq!(
+        tokens,
+        vec![
+          Token::Binding("x₁"),
+          Token::Binding("x₂"),
+          Token::Binding("y₁₀"),
+        ]
+      );
+      assert_ne!(tokens[0], tokens[1]);
+
+      let tokens: Vec<_> = Lexer::new("₁ x").collect();
+      assert_eq!(tokens, vec![Token::Invalid('₁'), Token::Binding("x")]);
+    }
+
+    #[test]
+    fn next_token_primes() {
+      let tokens: Vec<_> = Lexer::new("x' f'' y'z λx₁'. x₁'").collect();
+      assert_eq!(
+        tokens,
+        vec![
+          Token::Binding("x'"),
+          Token::Binding("f''"),
+          Token::Binding("y'z"),
+          Token::Lambda('λ'),
+          Token::Binding("x₁'"),
+          Token::Dot,
+          Token::Binding("x₁'"),
+        ]
+      );
+      let tokens: Vec<_> = Lexer::new("'x `let'`").collect();
+      assert_eq!(
+        tokens,
+        vec![
+          Token::Invalid('\''),
+          Token::Binding("x"),
+          Token::Binding("let'"),
+        ]
+      );
+    }
+
+    #[test]
+    fn next_token_lambda_adjacent_unicode() {
+      // 'λ' is never part of an identifier, even squashed between letters
+      let tokens: Vec<_> = Lexer::new("λα.αλβ.β").collect();
+      assert_eq!(
+        tokens,
+        vec![
+          Token::Lambda('λ'),
+          Token::Binding("α"),
+          Token::Dot,
+          Token::Binding("α"),
+          Token::Lambda('λ'),
+          Token::Binding("β"),
+          Token::Dot,
+          Token::Binding("β"),
+        ]
+      );
+    }
+
+    #[test]
+    fn tokenize() {
+      assert_eq!(
+        Lexer::new("λx. x").tokenize(),
+        vec![
+          Token::Lambda('λ'),
+          Token::Binding("x"),
+          Token::Dot,
+          Token::Binding("x"),
+        ]
+      );
+      assert_eq!(Lexer::new("  -- nothing").tokenize(), vec![]);
+    }
+
+    #[test]
+    fn trivia_tokens() {
+      let tokens = Lexer::new("x -- c\n y").with_trivia().tokenize();
+      assert_eq!(
+        tokens,
+        vec![
+          Token::Binding("x"),
+          Token::Trivia(" -- c\n "),
+          Token::Binding("y"),
+        ]
+      );
+      // nothing is lost, so the input can be rebuilt exactly
+      let input = "  -- id\nid = λx.x;\n\n-- done";
+      let tokens = Lexer::new(input).with_trivia().tokenize();
+      assert_eq!(tokens.first(), Some(&Token::Trivia("  -- id\n")));
+      assert_eq!(tokens.last(), Some(&Token::Trivia("\n\n-- done")));
+      let rebuilt: String = tokens.iter().map(Token::to_string).collect();
+      assert_eq!(rebuilt, input);
+      // and dropped by default
+      assert_eq!(Lexer::new("x -- c\n y").tokenize().len(), 2);
+    }
+
+    #[test]
+    fn peek_token() {
+      let mut lexer = Lexer::new(" λx. x");
+      assert_eq!(lexer.peek_token(), Some(&Token::Lambda('λ')));
+      assert_eq!(lexer.peek_token(), Some(&Token::Lambda('λ')));
+      assert_eq!(lexer.offset(), 1);
+      assert_eq!(lexer.next(), Some(Token::Lambda('λ')));
+      assert_eq!(lexer.peek_token(), Some(&Token::Binding("x")));
+      assert_eq!(
+        lexer.spanned().collect::<Vec<_>>(),
+        vec![
+          (Token::Binding("x"), Span { start: 3, end: 4 }),
+          (Token::Dot, Span { start: 4, end: 5 }),
+          (Token::Binding("x"), Span { start: 6, end: 7 }),
+        ]
+      );
+      let mut lexer = Lexer::new("-- nothing");
+      assert_eq!(lexer.peek_token(), None);
+      assert_eq!(lexer.next(), None);
+    }
+
+    #[test]
+    fn offset() {
+      // 'λ' takes up two bytes
+      let mut lexer = Lexer::new("λx. x");
+      assert_eq!(lexer.offset(), 0);
+      assert_eq!(lexer.next_token(), Some(Token::Lambda('λ')));
+      assert_eq!(lexer.offset(), 2);
+      assert_eq!(lexer.next_token(), Some(Token::Binding("x")));
+      assert_eq!(lexer.offset(), 3);
+      assert_eq!(lexer.next_token(), Some(Token::Dot));
+      assert_eq!(lexer.offset(), 4);
+      assert_eq!(lexer.next_token(), Some(Token::Binding("x")));
+      assert_eq!(lexer.offset(), 6);
+      assert_eq!(lexer.next_token(), None);
+      assert_eq!(lexer.offset(), 6);
+    }
+  }
+
+  mod spanned {
+    use super::*;
+
+    #[test]
+    fn tokenize_spanned() {
+      assert_eq!(
+        Lexer::new("λx. x").tokenize_spanned(),
+        vec![
+          (Token::Lambda('λ'), Span { start: 0, end: 2 }),
+          (Token::Binding("x"), Span { start: 2, end: 3 }),
+          (Token::Dot, Span { start: 3, end: 4 }),
+          (Token::Binding("x"), Span { start: 5, end: 6 }),
+        ]
+      );
+    }
+
+    #[test]
+    fn spanned_positions() {
+      let spans: Vec<_> = Lexer::new(" λx. (fx)").spanned().collect();
+      assert_eq!(
+        spans,
+        vec![
+          (Token::Lambda('λ'), Span { start: 1, end: 3 }),
+          (Token::Binding("x"), Span { start: 3, end: 4 }),
+          (Token::Dot, Span { start: 4, end: 5 }),
+          (Token::LParen, Span { start: 6, end: 7 }),
+          (Token::Binding("fx"), Span { start: 7, end: 9 }),
+          (Token::RParen, Span { start: 9, end: 10 }),
+        ]
+      );
+    }
+
+    #[test]
+    fn spanned_invalid_offset() {
+      let tokens = Lexer::new("x @ y").tokenize_spanned();
+      assert_eq!(tokens[1], (Token::Invalid('@'), Span { start: 2, end: 3 }));
+      // a number too big for `u64` is reported from its first digit
+      let tokens = Lexer::new("λ 99999999999999999999").tokenize_spanned();
+      assert_eq!(tokens[1], (Token::Invalid('9'), Span { start: 3, end: 23 }));
+    }
+
+    #[test]
+    fn spanned_skips_comments() {
+      let mut spanned = Lexer::new("-- note\nx").spanned();
+      assert_eq!(
+        spanned.next(),
+        Some((Token::Binding("x"), Span { start: 8, end: 9 }))
+      );
+      assert_eq!(spanned.next(), None);
+    }
+  }
+
+  mod from_bytes {
+    use super::*;
+
+    #[test]
+    fn from_bytes_matches_new() {
+      for input in ["λx. x₁ y", "let id = \\x -> x in id 3", ""] {
+        let lexer = Lexer::from_bytes(input.as_bytes()).unwrap();
+        assert_eq!(
+          lexer.tokenize_spanned(),
+          Lexer::new(input).tokenize_spanned()
+        );
+      }
+    }
+
+    #[test]
+    fn from_bytes_invalid_utf8() {
+      // a lone continuation byte, then 'λ' cut short
+      for input in [&b"x \x80 y"[..], &b"\xce"[..]] {
+        let err = Lexer::from_bytes(input).err().unwrap();
+        assert_eq!(Some(err), str::from_utf8(input).err());
+      }
+    }
+  }
+
+  mod ascii_fast_path {
+    use super::*;
+
+    use crate::rng::Rng;
+
+    /// Lex as the char-based path would, however plain the input
+    fn char_based(input: &str, trivia: bool) -> Vec<(Token<'_>, Span)> {
+      let mut lexer = Lexer::new(input);
+      lexer.ascii = false;
+      lexer.trivia = trivia;
+      lexer.tokenize_spanned()
+    }
+
+    fn assert_same(input: &str) {
+      assert!(input.is_ascii());
+      for trivia in [false, true] {
+        let mut lexer = Lexer::new(input);
+        lexer.trivia = trivia;
+        assert!(lexer.ascii);
+        assert_eq!(
+          lexer.tokenize_spanned(),
+          char_based(input, trivia),
+          "{input:?}"
+        );
+      }
+    }
+
+    #[test]
+    fn ascii_matches_char_based() {
+      for input in [
+        "",
+        "   ",
+        "\\x. x",
+        "(\\f -> f f) (g = h;)",
+        "\\a, b. a",
+        "let `in` = a in `in`",
+        "x1 y2z 3x 3.x 007",
+        "18446744073709551615 18446744073709551616 x",
+        "- -> -x --> x",
+        "-- comment\n  x -- trailing\r\n\ty --",
+        "`` `1` `x `x1` `X` ` #",
+        "Abc 'x' \u{0} {}",
+      ] {
+        assert_same(input);
+      }
+    }
+
+    #[test]
+    fn ascii_matches_char_based_random() {
+      // weighted towards bytes that mean something, to hit tokens more often
+      let alphabet = b"  \n\t\r()\\.=;,->`-xyzlet in0123456789aAZ#_'\"{";
+      let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+      for _ in 0..2000 {
+        let len = rng.below(40);
+        let input: String = (0..len)
+          .map(|_| char::from(alphabet[rng.below(alphabet.len())]))
+          .collect();
+        assert_same(&input);
+      }
+    }
+
+    #[test]
+    fn ascii_peek_and_offset() {
+      let mut lexer = Lexer::new("  f  x");
+      assert!(lexer.ascii);
+      assert_eq!(lexer.offset(), 0);
+      assert_eq!(lexer.peek_token(), Some(&Token::Binding("f")));
+      assert_eq!(lexer.offset(), 2);
+      assert_eq!(lexer.next_token(), Some(Token::Binding("f")));
+      assert_eq!(lexer.offset(), 3);
+      assert_eq!(lexer.next_token(), Some(Token::Binding("x")));
+      assert_eq!(lexer.offset(), 6);
+      assert_eq!(lexer.next_token(), None);
+    }
+
+    #[test]
+    fn non_ascii_falls_back() {
+      let lexer = Lexer::new("λx. x₁");
+      assert!(!lexer.ascii);
+      assert_eq!(
+        lexer.tokenize(),
+        [
+          Token::Lambda('λ'),
+          Token::Binding("x"),
+          Token::Dot,
+          Token::Binding("x₁")
+        ]
+      );
+    }
+  }
+
+  mod owned_lexer {
+    use super::*;
+
+    use std::io::Cursor;
+
+    #[test]
+    fn owned_lexer_matches_lexer() {
+      let input = "id = λx. x; -- identity\n\n  const = \\x -> \\y. x;\nconst id";
+      let owned = OwnedLexer::new(Cursor::new(input))
+        .collect::<io::Result<Vec<_>>>()
+        .unwrap();
+      let borrowed: Vec<_> = Lexer::new(input).map(OwnedToken::from).collect();
+      assert_eq!(owned, borrowed);
+    }
+
+    #[test]
+    fn owned_lexer_feeds_parser() {
+      use crate::parser::Parser;
+      use crate::term::Term;
+
+      let owned = OwnedLexer::new(Cursor::new("(λx.\n x)\n y"))
+        .collect::<io::Result<Vec<_>>>()
+        .unwrap();
+      let ast = Parser::new(owned.iter().map(OwnedToken::as_token)).parse();
+      assert_eq!(
+        ast,
+        Ok(Term::app(Term::abs("x", Term::var("x")), Term::var("y")))
+      );
+    }
+
+    #[test]
+    fn owned_lexer_empty() {
+      assert_eq!(OwnedLexer::new(Cursor::new("")).count(), 0);
+      assert_eq!(OwnedLexer::new(Cursor::new("\n  \n-- x\n")).count(), 0);
+    }
+  }
+
+  mod token_map {
+    use super::*;
+
+    use std::collections::HashMap;
+
+    use crate::parser::Parser;
+    use crate::term::Term;
+
+    #[test]
+    fn token_map_renames_bindings() {
+      let upper = HashMap::from([("f", "F"), ("x", "X")]);
+      let lexer = Lexer::new("λx. f x");
+      let renamed = TokenMap::new(lexer, |token| match token {
+        Token::Binding(name) => Token::Binding(upper[name]),
+        other => other,
+      });
+      let ast = Parser::new(renamed).parse();
+      assert_eq!(
+        ast,
+        Ok(Term::abs("X", Term::app(Term::var("F"), Term::var("X"))))
+      );
+    }
+
+    #[test]
+    fn token_map_leaves_structure() {
+      let mapped: Vec<_> = TokenMap::new(Lexer::new("(λx. x)"), |token| token).collect();
+      let plain: Vec<_> = Lexer::new("(λx. x)").collect();
+      assert_eq!(mapped, plain);
+    }
   }
 }
@@ -7,21 +7,49 @@
 //! # Backus-Naur form Grammar
 //!
 //! ```text
+//! prog ::= BIND EQUALS term SEMICOLON prog
+//!        | term
+//!        | term SEMICOLON
+//!
 //! term ::= appl
-//!        | LAMBDA BIND DOT term
+//!        | LAMBDA bind sep term
+//!        | LET BIND EQUALS term IN term
+//!
+//! bind ::= BIND
+//!        | BIND bind
+//!        | BIND COMMA bind
+//!
+//! sep  ::= DOT
+//!        | ARROW
 //!
 //! appl ::= appl atom
 //!        | atom
 //!
 //! atom ::= LPAREN term RPAREN
 //!        | BIND
+//!        | NUM
 //! ```
 //!
-//! where our atoms are simply 'LAMBDA', 'BIND', 'DOT', 'LPAREN', and 'RPAREN'
+//! where our atoms are simply 'LAMBDA', 'BIND', 'DOT', 'ARROW', 'LPAREN', 'RPAREN',
+//! 'EQUALS', 'SEMICOLON', 'COMMA', the keywords 'LET' and 'IN', and 'NUM', a decimal
+//! literal standing for the Church numeral of that value. A name spelling a keyword
+//! is written escaped, as in `` `let` ``. Programs (`prog`) are only accepted by `Parser::parse_program`
+//! and `Parser::parse_program_resilient`
 //!
 
+pub mod api;
+pub mod arena;
+pub mod codec;
+pub mod fresh;
 pub mod interpreter;
 pub mod lexer;
 pub mod parser;
+pub mod prelude;
+#[cfg(test)]
+pub(crate) mod rng;
+pub mod ski;
+pub mod symbol;
 pub mod term;
+pub mod testing;
 pub mod token;
+pub mod visit;
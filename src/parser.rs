@@ -1,40 +1,270 @@
 //!
 //! Handles conversion from `Iterator<Item=Token<'src>>` to `Term<'src>`
 //!
+//! Malformed input of any kind is reported as a `ParseError`, never a panic,
+//! pathologically deep nesting included (see `Parser::with_max_nesting`)
+//!
+
+use std::error::Error;
+use std::fmt;
+use std::iter::{Chain, Cloned};
+use std::option;
+use std::rc::Rc;
+use std::slice;
+use std::vec;
 
+use crate::lexer::Lexer;
+use crate::prelude::numeral;
 use crate::term::Term;
-use crate::token::Token;
+use crate::token::{Span, Token};
 
 /// Our parser can fail, so we must have some way to represent failure
 #[derive(Debug, PartialEq)]
 pub enum ParseError<'src> {
   /// We ran out of input while in the middle of parsing something
-  UnexpectedEof,
-  /// We ran into a token we didn't expect to see
-  UnexpectedToken(Token<'src>),
+  /// - `expected` describes what should have come next, when we know
+  UnexpectedEof { expected: Option<&'static str> },
+  /// We ran into a token we didn't expect to see, `at` being its index in the
+  /// token stream
+  UnexpectedToken { token: Token<'src>, at: usize },
+  /// Input ended before the `(` at token index `opened_at` was closed
+  UnclosedParen { opened_at: usize },
+  /// Nothing that could be a body followed the `.` (or `->`) of the abstraction
+  /// whose `λ` is at token index `at`, as in `λx.`
+  MissingAbstractionBody { at: usize },
+  /// A definition's name was referred to before (or within) its own definition
+  UsedBeforeDefinition(&'src str),
+  /// A `)` at token index `at` with no `(` left open for it to close, as in `x )`
+  UnmatchedCloseParen { at: usize },
+  /// Parentheses, abstractions and `let`s nested deeper than the parser's limit,
+  /// the one opened at token index `at` being too many
+  NestingTooDeep { at: usize },
+}
+
+impl<'src> fmt::Display for ParseError<'src> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      ParseError::UnexpectedEof { expected: None } => write!(f, "unexpected end of input"),
+      ParseError::UnexpectedEof {
+        expected: Some(expected),
+      } => write!(f, "unexpected end of input, expected {}", expected),
+      ParseError::UnexpectedToken {
+        token: Token::Invalid(c),
+        ..
+      } => write!(f, "invalid character `{}`", c.escape_debug()),
+      ParseError::UnexpectedToken { token, .. } => write!(f, "unexpected token `{}`", token),
+      ParseError::UnclosedParen { .. } => write!(f, "unclosed `(`, expected `)`"),
+      ParseError::UnmatchedCloseParen { .. } => write!(f, "unmatched `)`"),
+      ParseError::MissingAbstractionBody { .. } => write!(f, "abstraction is missing a body"),
+      ParseError::UsedBeforeDefinition(name) => {
+        write!(f, "`{}` was used before it was defined", name)
+      }
+      ParseError::NestingTooDeep { .. } => write!(f, "terms nested too deeply"),
+    }
+  }
+}
+
+impl<'src> Error for ParseError<'src> {}
+
+impl<'src> ParseError<'src> {
+  /// Render the error against the source that was parsed, showing the offending
+  /// line with the region responsible underlined, like
+  ///
+  /// ```text
+  /// unexpected token `)`
+  ///  --> 1:3
+  ///   |
+  /// 1 | x )
+  ///   |   ^
+  /// ```
+  ///
+  /// Only meaningful when `src` is what produced the parser's tokens
+  pub fn render(&self, src: &str) -> String {
+    let Some(span) = self.span(src) else {
+      return self.to_string();
+    };
+    let line_start = src[..span.start].rfind('\n').map_or(0, |idx| idx + 1);
+    let line_end = src[span.start..]
+      .find('\n')
+      .map_or(src.len(), |idx| span.start + idx);
+    let line_number = src[..line_start].matches('\n').count() + 1;
+    // count chars rather than bytes, so multi-byte glyphs like 'λ' line up
+    let column = src[line_start..span.start].chars().count();
+    let width = src[span.start..span.end.min(line_end)]
+      .chars()
+      .count()
+      .max(1);
+    let gutter = " ".repeat(line_number.to_string().len());
+    format!(
+      "{self}\n{gutter}--> {line_number}:{}\n{gutter} |\n{line_number} | {}\n{gutter} | {}{}",
+      column + 1,
+      &src[line_start..line_end],
+      " ".repeat(column),
+      "^".repeat(width),
+    )
+  }
+
+  /// Find the region of the source responsible for the error
+  fn span(&self, src: &str) -> Option<Span> {
+    match self {
+      ParseError::UnexpectedEof { .. } => {
+        // point just past the last thing written
+        let end = src.trim_end().len();
+        Some(Span { start: end, end })
+      }
+      ParseError::UnexpectedToken { at, .. }
+      | ParseError::UnclosedParen { opened_at: at }
+      | ParseError::MissingAbstractionBody { at }
+      | ParseError::UnmatchedCloseParen { at }
+      | ParseError::NestingTooDeep { at } => {
+        Lexer::new(src).spanned().nth(*at).map(|(_, span)| span)
+      }
+      ParseError::UsedBeforeDefinition(_) => None,
+    }
+  }
 }
 
 /// Explicit return type for functions that do parsing, to distinguish them
 pub type ParseResult<'src> = Result<Term<'src>, ParseError<'src>>;
 
+/// A sequence of named definitions followed by the expression they're for
+#[derive(Debug, PartialEq)]
+pub struct Program<'src> {
+  /// Definitions in the order they were written, each with every earlier
+  /// definition already substituted in
+  pub definitions: Vec<(&'src str, Term<'src>)>,
+  /// Final expression, with every definition substituted in
+  pub body: Term<'src>,
+}
+
+impl<'src> Program<'src> {
+  /// Look up what a name was (most recently) defined as
+  pub fn definition(&self, name: &str) -> Option<&Term<'src>> {
+    self
+      .definitions
+      .iter()
+      .rev()
+      .find(|(defined, _)| *defined == name)
+      .map(|(_, term)| term)
+  }
+}
+
 /// Process all tokens in provided iterator
 pub struct Parser<'src, I> {
   tokens: I,
   current_token: Option<Token<'src>>,
+  /// Index of `current_token` in the stream
+  position: usize,
+  /// Whether trivia with a line break came just before `current_token`, only
+  /// ever the case for tokens from `Lexer::with_trivia`
+  line_break: bool,
+  /// Whether an abstraction may leave out its separator, see `with_optional_separator`
+  lenient: bool,
+  /// How many parentheses, abstractions and `let`s are currently open
+  depth: usize,
+  /// How many of those are parentheses, to tell whether a `)` closes anything
+  open_parens: usize,
+  max_nesting: Option<usize>,
+  associativity: Associativity,
+  body_scope: BodyScope,
+}
+
+/// Which way a run of applications like `x y z` groups, see `Parser::with_associativity`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Associativity {
+  /// `(x y) z`, as is standard
+  #[default]
+  Left,
+  /// `x (y z)`
+  Right,
+}
+
+/// How far an abstraction's body extends, see `Parser::with_body_scope`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum BodyScope {
+  /// As far right as possible, so `λx. a b` is `λx. (a b)`, as is standard
+  #[default]
+  Maximal,
+  /// Just the one atom, so `λx. a b` is `(λx. a) b`, and a longer body needs
+  /// parentheses, as in `λx. (a b)`
+  Atom,
+}
+
+impl<'src> Parser<'src, vec::IntoIter<Token<'src>>> {
+  /// Parse a pre-built list of tokens, for when they don't come from a `Lexer`
+  pub fn from_tokens(tokens: Vec<Token<'src>>) -> Self {
+    Self::new(tokens.into_iter())
+  }
+}
+
+impl<'a, 'src> Parser<'src, Cloned<slice::Iter<'a, Token<'src>>>> {
+  /// Parse a borrowed run of tokens, e.g. ones assembled programmatically
+  pub fn from_slice(tokens: &'a [Token<'src>]) -> Self {
+    Self::new(tokens.iter().cloned())
+  }
 }
 
 impl<'src, I> Parser<'src, I>
 where
   I: Iterator<Item = Token<'src>>,
 {
+  /// Parse the tokens `tokens` yields, up to a `Token::Eof` if there is one,
+  /// skipping any `Token::Trivia`
   pub fn new(mut tokens: I) -> Self {
-    let current_token = tokens.next();
+    let (current_token, line_break) = Self::pull(&mut tokens);
     Self {
       tokens,
       current_token,
+      position: 0,
+      line_break,
+      lenient: false,
+      depth: 0,
+      open_parens: 0,
+      max_nesting: None,
+      associativity: Associativity::Left,
+      body_scope: BodyScope::Maximal,
     }
   }
 
+  /// Group runs of applications this way
+  /// - this changes what a term means, not just how it's written, and terms
+  ///   always display left-associatively, so `Associativity::Right` won't read
+  ///   back what it displays
+  pub fn with_associativity(mut self, associativity: Associativity) -> Self {
+    self.associativity = associativity;
+    self
+  }
+
+  /// Scope abstraction bodies this way
+  /// - like `with_associativity`, this changes what a term means, and terms
+  ///   always display with maximal bodies, so `BodyScope::Atom` won't read back
+  ///   what it displays
+  /// - the body of a `let` always extends as far as possible
+  pub fn with_body_scope(mut self, body_scope: BodyScope) -> Self {
+    self.body_scope = body_scope;
+    self
+  }
+
+  /// Fail with `ParseError::NestingTooDeep` rather than nest parentheses,
+  /// abstractions and `let`s deeper than this, since each level takes some native
+  /// stack and running out of it aborts the process
+  /// - unlimited unless set, as a term this crate displays can nest as deeply as it
+  ///   likes, e.g. `prelude::numeral(n)` nests `n` times
+  /// - an abstraction binding several names at once counts once
+  pub fn with_max_nesting(mut self, max_nesting: usize) -> Self {
+    self.max_nesting = Some(max_nesting);
+    self
+  }
+
+  /// Accept abstractions without a `.` (or `->`), as in `λx x` for `λx. x`, the
+  /// body starting straight after the binder
+  /// - there's then no telling binders from body, so `λx y z` is `λx. y z`, and
+  ///   binding several names still needs a separator
+  pub fn with_optional_separator(mut self) -> Self {
+    self.lenient = true;
+    self
+  }
+
   /// Try to convert the parser's provided iterator into some `Term<'src>`
   pub fn parse(&mut self) -> ParseResult<'src> {
     let expr = self.parse_application()?;
@@ -42,82 +272,502 @@ where
     Ok(expr)
   }
 
+  /// Parse a script of `name = term;` definitions ending in a final term, e.g.
+  /// `id = λx. x; const = λx. λy. x; const id`
+  /// - a trailing `;` after the final term is allowed
+  /// - a name may be redefined, the new definition seeing the old one
+  pub fn parse_program(&mut self) -> Result<Program<'src>, ParseError<'src>> {
+    let mut definitions: Vec<(&'src str, Term<'src>)> = Vec::new();
+    loop {
+      let head = match self.peek() {
+        Some(Token::Binding(name)) => Some(*name),
+        _ => None,
+      };
+      let term = self.parse_application()?;
+      if self.peek() != Some(&Token::Equals) {
+        // just the final expression left
+        if self.peek() == Some(&Token::Semicolon) {
+          self.next();
+        }
+        let () = self.eof()?;
+        let body = Self::inline(&definitions, term);
+        return Ok(Program { definitions, body });
+      }
+      let (name, definition) = self.parse_definition(&definitions, head, term)?;
+      let () = self.eat(Token::Semicolon)?;
+      definitions.push((name, definition));
+    }
+  }
+
+  /// Parse a program like `parse_program`, but carry on past a malformed statement
+  /// by skipping to the next `;` or line break, collecting the error instead
+  /// - gives the terms of statements that aren't definitions, each with earlier
+  ///   definitions substituted in, as in `Program::body`
+  /// - line breaks are only seen in tokens from `Lexer::with_trivia`, otherwise
+  ///   a statement missing its `;` takes the next one down with it
+  /// - only errors resync at a line break, a statement without them still needs
+  ///   its `;`
+  pub fn parse_program_resilient(&mut self) -> (Vec<Term<'src>>, Vec<ParseError<'src>>) {
+    let mut definitions: Vec<(&'src str, Term<'src>)> = Vec::new();
+    let mut terms = Vec::new();
+    let mut errors = Vec::new();
+    while self.peek().is_some() {
+      let start = self.position;
+      match self.parse_statement(&definitions) {
+        Ok((Some(name), definition)) => definitions.push((name, definition)),
+        Ok((None, term)) => terms.push(term),
+        Err(error) => {
+          self.depth = 0;
+          self.open_parens = 0;
+          // the `;` may have been what was unexpected, and it's only gone already
+          // if the parser got past it, otherwise it's still next and skipped below
+          let consumed = matches!(
+            error,
+            ParseError::UnexpectedToken {
+              token: Token::Semicolon,
+              at,
+            } if at < self.position
+          );
+          // a line break already reached still counts only if the statement got
+          // somewhere, or the same error would come straight back
+          let resynced = consumed || (self.line_break && self.position > start);
+          if !resynced {
+            // up to and including the next `;`, or up to the next line
+            while let Some(token) = self.next() {
+              if token == Token::Semicolon || self.line_break {
+                break;
+              }
+            }
+          }
+          errors.push(error);
+        }
+      }
+    }
+    (terms, errors)
+  }
+
+  /// A single statement of a program, with the name it defines if it's a definition
+  /// - the final statement needn't end in a `;`
+  fn parse_statement(
+    &mut self,
+    definitions: &[(&'src str, Term<'src>)],
+  ) -> Result<(Option<&'src str>, Term<'src>), ParseError<'src>> {
+    let head = match self.peek() {
+      Some(Token::Binding(name)) => Some(*name),
+      _ => None,
+    };
+    let term = self.parse_application()?;
+    let statement = if self.peek() == Some(&Token::Equals) {
+      let (name, definition) = self.parse_definition(definitions, head, term)?;
+      (Some(name), definition)
+    } else {
+      (None, Self::inline(definitions, term))
+    };
+    if self.peek().is_some() {
+      let () = self.eat(Token::Semicolon)?;
+    }
+    Ok(statement)
+  }
+
+  /// The rest of `name = term`, once `term` has been parsed as the name it defines
+  /// and the `=` is next
+  fn parse_definition(
+    &mut self,
+    definitions: &[(&'src str, Term<'src>)],
+    head: Option<&'src str>,
+    term: Term<'src>,
+  ) -> Result<(&'src str, Term<'src>), ParseError<'src>> {
+    // only a lone name may be defined
+    let name = match (head, term) {
+      (Some(name), Term::Variable(var)) if var == name => name,
+      _ => {
+        return Err(ParseError::UnexpectedToken {
+          token: Token::Equals,
+          at: self.position,
+        })
+      }
+    };
+    let () = self.eat(Token::Equals)?;
+    let definition = Self::inline(definitions, self.parse_application()?);
+    // anything still free was never defined, so it can't be defined now
+    let used_early = definitions
+      .iter()
+      .map(|(_, earlier)| earlier)
+      .chain([&definition])
+      .any(|term| term.free_variables().contains(name));
+    if used_early {
+      return Err(ParseError::UsedBeforeDefinition(name));
+    }
+    Ok((name, definition))
+  }
+
+  /// Parse independent terms separated by `;`, e.g. `x; y; z`, REPL-style
+  /// - a trailing `;` is allowed, and empty input gives no terms
+  pub fn parse_sequence(&mut self) -> Result<Vec<Term<'src>>, ParseError<'src>> {
+    let mut terms = Vec::new();
+    while self.peek().is_some() {
+      terms.push(self.parse_application()?);
+      if self.peek().is_some() {
+        let () = self.eat(Token::Semicolon)?;
+      }
+    }
+    Ok(terms)
+  }
+
+  /// Parse a term built only from variables, application and parentheses, such
+  /// as SKI combinator expressions like `s k k`
+  /// - any abstraction is rejected, reported at its `λ`
+  pub fn parse_combinatory(&mut self) -> ParseResult<'src> {
+    let expr = self.parse_combinatory_application()?;
+    let () = self.eof()?;
+    Ok(expr)
+  }
+
+  fn parse_combinatory_application(&mut self) -> ParseResult<'src> {
+    let head = self.parse_combinatory_atom()?;
+    let mut args = Vec::new();
+    while matches!(self.peek(), Some(Token::Binding(_)) | Some(Token::LParen)) {
+      args.push(self.parse_combinatory_atom()?);
+    }
+    Ok(self.apply(head, args))
+  }
+
+  fn parse_combinatory_atom(&mut self) -> ParseResult<'src> {
+    match self.peek() {
+      Some(Token::Binding(_)) => Ok(Term::Variable(self.eat_binding()?.into())),
+      Some(Token::LParen) => self.nested(|parser| {
+        let opened_at = parser.position;
+        let () = parser.eat(Token::LParen)?;
+        parser.open_parens += 1;
+        let term = parser.parse_combinatory_application()?;
+        if parser.peek().is_none() {
+          return Err(ParseError::UnclosedParen { opened_at });
+        }
+        let () = parser.eat(Token::RParen)?;
+        parser.open_parens -= 1;
+        Ok(term)
+      }),
+      Some(tok) => Err(self.unexpected(tok.clone(), self.position)),
+      None => Err(ParseError::UnexpectedEof {
+        expected: Some("a variable or `(`"),
+      }),
+    }
+  }
+
+  /// Parse a single atom, i.e. a variable, numeral, parenthesized term,
+  /// abstraction or `let`, leaving whatever follows unconsumed, e.g. for a host
+  /// language embedding terms to carry on from
+  /// - an abstraction or `let` still takes its body as far as it extends, so
+  ///   only applications stop short
+  ///
+  /// ```
+  /// use simple::lexer::Lexer;
+  /// use simple::parser::Parser;
+  /// use simple::term::Term;
+  /// use simple::token::Token;
+  ///
+  /// let mut parser = Parser::new(Lexer::new("x y"));
+  /// assert_eq!(parser.parse_one_atom(), Ok(Term::var("x")));
+  /// assert_eq!(parser.peek(), Some(&Token::Binding("y")));
+  /// ```
+  pub fn parse_one_atom(&mut self) -> ParseResult<'src> {
+    self.parse_atom()
+  }
+
+  /// Give up on parsing, handing back every token not yet consumed, starting
+  /// with the one `peek` shows
+  /// - trivia after that one comes through as the underlying iterator yields it
+  pub fn into_remaining(self) -> Chain<option::IntoIter<Token<'src>>, I> {
+    self.current_token.into_iter().chain(self.tokens)
+  }
+
+  /// Substitute every definition made so far into a term
+  fn inline(definitions: &[(&'src str, Term<'src>)], term: Term<'src>) -> Term<'src> {
+    definitions.iter().fold(term, |term, (name, definition)| {
+      term.substitute(name, definition)
+    })
+  }
+
   fn parse_atom(&mut self) -> ParseResult<'src> {
     match self.peek() {
-      Some(Token::Binding(_)) => Ok(Term::Variable(self.eat_binding()?)),
-      Some(Token::LParen) => self.parse_parenthesized(),
-      Some(Token::Lambda) => self.parse_abstraction(),
-      Some(tok) => Err(ParseError::UnexpectedToken(tok.clone())),
-      None => Err(ParseError::UnexpectedEof),
+      Some(Token::Binding(_)) => Ok(Term::Variable(self.eat_binding()?.into())),
+      // sugar for the Church numeral, see `prelude::numeral`
+      Some(&Token::Number(n)) => {
+        self.next();
+        Ok(numeral(n))
+      }
+      Some(Token::LParen) => self.nested(Self::parse_parenthesized),
+      Some(Token::Lambda(_)) => self.nested(Self::parse_abstraction),
+      Some(Token::Let) => self.nested(Self::parse_let),
+      Some(tok) => Err(self.unexpected(tok.clone(), self.position)),
+      None => Err(ParseError::UnexpectedEof {
+        expected: Some("a term"),
+      }),
     }
   }
 
+  /// An abstraction, possibly binding several names at once as sugar for nested
+  /// ones, so `λx y. t` and `λx, y. t` both mean `λx. λy. t`
+  /// - commas and spaces may be mixed freely, but a comma must be followed by
+  ///   another name
   fn parse_abstraction(&mut self) -> ParseResult<'src> {
-    let () = self.eat(Token::Lambda)?;
-    let param = self.eat_binding()?;
-    let () = self.eat(Token::Dot)?;
+    let at = self.position;
+    let () = self.eat_lambda()?;
+    let mut params = vec![self.eat_binding()?];
+    let mut commas = false;
+    loop {
+      match self.peek() {
+        Some(Token::Binding(_)) => params.push(self.eat_binding()?),
+        Some(Token::Comma) => {
+          self.next();
+          commas = true;
+          params.push(self.eat_binding()?);
+        }
+        _ => break,
+      }
+    }
+    let separated = matches!(self.peek(), Some(Token::Dot | Token::Arrow));
+    if self.lenient && !separated && !commas {
+      // only the first name was a binder, any others start the body
+      let mut rest: Vec<_> = params.split_off(1).into_iter().map(Term::var).collect();
+      if rest.is_empty() {
+        let () = self.expect_body(at)?;
+        let body = self.parse_body()?;
+        return Ok(Term::abs(params[0], body));
+      }
+      if self.body_scope == BodyScope::Atom {
+        // the body is just the first of them, the abstraction is applied to the rest
+        let body = rest.remove(0);
+        return Ok(self.apply(Term::abs(params[0], body), rest));
+      }
+      let body = self.parse_application_from(rest)?;
+      return Ok(Term::abs(params[0], body));
+    }
+    let () = self.eat_separator()?;
+    let () = self.expect_body(at)?;
+    let body = self.parse_body()?;
+    Ok(
+      params
+        .into_iter()
+        .rev()
+        .fold(body, |body, param| Term::Abstraction {
+          param: param.into(),
+          body: Rc::new(body),
+        }),
+    )
+  }
+
+  /// The body of an abstraction, as far as the parser's `BodyScope` allows
+  fn parse_body(&mut self) -> ParseResult<'src> {
+    match self.body_scope {
+      BodyScope::Maximal => self.parse_application(),
+      BodyScope::Atom => self.parse_atom(),
+    }
+  }
+
+  /// A local definition, `let x = v in t`, which is sugar for `(λx. t) v`
+  /// - like an abstraction body, `t` extends as far right as possible
+  fn parse_let(&mut self) -> ParseResult<'src> {
+    let () = self.eat(Token::Let)?;
+    let name = self.eat_binding()?;
+    let () = self.eat(Token::Equals)?;
+    let value = self.parse_application()?;
+    let () = self.eat(Token::In)?;
     let body = self.parse_application()?;
-    Ok(Term::Abstraction {
-      param,
-      body: Box::new(body),
-    })
+    Ok(Term::app(Term::abs(name, body), value))
+  }
+
+  /// Check the body of an abstraction isn't simply left out, the input or the
+  /// enclosing term ending straight after the separator
+  /// - any other token is left for `parse_atom` to report as unexpected
+  fn expect_body(&self, at: usize) -> Result<(), ParseError<'src>> {
+    match self.peek() {
+      None | Some(Token::RParen | Token::Semicolon) => {
+        Err(ParseError::MissingAbstractionBody { at })
+      }
+      _ => Ok(()),
+    }
+  }
+
+  /// Parse something that opens another level of nesting, failing if that's one
+  /// too many
+  fn nested(&mut self, parse: impl FnOnce(&mut Self) -> ParseResult<'src>) -> ParseResult<'src> {
+    if self.max_nesting.is_some_and(|max| self.depth >= max) {
+      return Err(ParseError::NestingTooDeep { at: self.position });
+    }
+    self.depth += 1;
+    let term = parse(self);
+    self.depth -= 1;
+    term
   }
 
   fn parse_application(&mut self) -> ParseResult<'src> {
-    let mut term = self.parse_atom()?;
+    let head = self.parse_atom()?;
+    self.parse_application_from(vec![head])
+  }
+
+  /// Continue a run of applications from atoms already parsed, which mustn't
+  /// be empty, then group the lot
+  fn parse_application_from(&mut self, mut atoms: Vec<Term<'src>>) -> ParseResult<'src> {
     // keep parsing while the next token can start an atom
-    while matches!(self.peek(), Some(Token::Binding(_)) | Some(Token::LParen)) {
-      let rhs = self.parse_atom()?;
-      term = Term::Application {
-        lhs: Box::new(term),
-        rhs: Box::new(rhs),
-      };
+    while matches!(
+      self.peek(),
+      Some(Token::Binding(_)) | Some(Token::Number(_)) | Some(Token::LParen)
+    ) {
+      atoms.push(self.parse_atom()?);
+    }
+    let head = atoms.remove(0);
+    Ok(self.apply(head, atoms))
+  }
+
+  /// Apply `head` to `args`, grouped according to the parser's associativity
+  fn apply(&self, head: Term<'src>, args: Vec<Term<'src>>) -> Term<'src> {
+    match self.associativity {
+      Associativity::Left => Term::from_spine(head, args),
+      Associativity::Right => {
+        let mut args = args;
+        match args.pop() {
+          Some(last) => {
+            let rhs = args
+              .into_iter()
+              .rev()
+              .fold(last, |rhs, lhs| Term::app(lhs, rhs));
+            Term::app(head, rhs)
+          }
+          None => head,
+        }
+      }
     }
-    Ok(term)
   }
 
   fn parse_parenthesized(&mut self) -> ParseResult<'src> {
+    let opened_at = self.position;
     let () = self.eat(Token::LParen)?;
+    self.open_parens += 1;
     let term = self.parse_application()?;
+    // running out here (rather than somewhere inside) means only the ')' is missing
+    if self.peek().is_none() {
+      return Err(ParseError::UnclosedParen { opened_at });
+    }
     let () = self.eat(Token::RParen)?;
+    self.open_parens -= 1;
     Ok(term)
   }
 
   fn eof(&mut self) -> Result<(), ParseError<'src>> {
-    if let Some(tok) = self.next() {
-      Err(ParseError::UnexpectedToken(tok))
+    let at = self.position;
+    if let Some(token) = self.next() {
+      Err(self.unexpected(token, at))
     } else {
       Ok(())
     }
   }
 
-  fn peek(&self) -> Option<&Token<'src>> {
+  /// Look at the next token without consuming it, e.g. to decide which `parse_*`
+  /// method to call
+  ///
+  /// ```
+  /// use simple::lexer::Lexer;
+  /// use simple::parser::Parser;
+  /// use simple::token::Token;
+  ///
+  /// let parser = Parser::new(Lexer::new("λx. x"));
+  /// assert_eq!(parser.peek(), Some(&Token::Lambda('λ')));
+  /// ```
+  pub fn peek(&self) -> Option<&Token<'src>> {
     self.current_token.as_ref()
   }
 
+  /// Whether every token has been consumed
+  pub fn is_eof(&self) -> bool {
+    self.current_token.is_none()
+  }
+
   fn next(&mut self) -> Option<Token<'src>> {
-    let token = self.current_token.clone();
-    self.current_token = self.tokens.next();
-    token
+    let token = self.current_token.take()?;
+    (self.current_token, self.line_break) = Self::pull(&mut self.tokens);
+    self.position += 1;
+    Some(token)
+  }
+
+  /// The error for running into `token` at index `at` when something else was
+  /// expected, singling out a `)` that closes nothing
+  fn unexpected(&self, token: Token<'src>, at: usize) -> ParseError<'src> {
+    match token {
+      Token::RParen if self.open_parens == 0 => ParseError::UnmatchedCloseParen { at },
+      token => ParseError::UnexpectedToken { token, at },
+    }
+  }
+
+  /// The next token that means anything, skipping trivia, and whether any of
+  /// that trivia broke the line
+  /// - an explicit end of input is the same as running out
+  fn pull(tokens: &mut I) -> (Option<Token<'src>>, bool) {
+    let mut line_break = false;
+    for token in tokens {
+      match token {
+        Token::Trivia(text) => line_break |= text.contains('\n'),
+        Token::Eof => return (None, line_break),
+        token => return (Some(token), line_break),
+      }
+    }
+    (None, line_break)
   }
 
-  fn next_eof(&mut self) -> Result<Token<'src>, ParseError<'src>> {
-    self.next().ok_or(ParseError::UnexpectedEof)
+  /// Consume the current token, along with its index for error reporting
+  /// - `expected` is what to report as missing if there isn't one
+  fn next_eof(
+    &mut self,
+    expected: Option<&'static str>,
+  ) -> Result<(Token<'src>, usize), ParseError<'src>> {
+    let at = self.position;
+    self
+      .next()
+      .map(|token| (token, at))
+      .ok_or(ParseError::UnexpectedEof { expected })
   }
 
   fn eat(&mut self, expected: Token<'src>) -> Result<(), ParseError<'src>> {
-    let actual = self.next_eof()?;
+    let description = match expected {
+      Token::LParen => Some("`(`"),
+      Token::RParen => Some("`)`"),
+      Token::Dot => Some("`.`"),
+      Token::Arrow => Some("`->`"),
+      Token::Equals => Some("`=`"),
+      Token::Semicolon => Some("`;`"),
+      Token::Comma => Some("`,`"),
+      Token::Let => Some("`let`"),
+      Token::In => Some("`in`"),
+      _ => None,
+    };
+    let (actual, at) = self.next_eof(description)?;
     if actual == expected {
       Ok(())
     } else {
-      Err(ParseError::UnexpectedToken(actual))
+      Err(self.unexpected(actual, at))
+    }
+  }
+
+  /// Consume the 'λ' (or '\\') introducing an abstraction
+  fn eat_lambda(&mut self) -> Result<(), ParseError<'src>> {
+    match self.next_eof(Some("`λ`"))? {
+      (Token::Lambda(_), _) => Ok(()),
+      (token, at) => Err(self.unexpected(token, at)),
+    }
+  }
+
+  /// Consume the '.' (or '->') between a binder and its body
+  fn eat_separator(&mut self) -> Result<(), ParseError<'src>> {
+    match self.next_eof(Some("`.` or `->`"))? {
+      (Token::Dot | Token::Arrow, _) => Ok(()),
+      (token, at) => Err(self.unexpected(token, at)),
     }
   }
 
   fn eat_binding(&mut self) -> Result<&'src str, ParseError<'src>> {
-    match self.next_eof()? {
-      Token::Binding(name) => Ok(name),
-      other => Err(ParseError::UnexpectedToken(other)),
+    match self.next_eof(Some("an identifier"))? {
+      (Token::Binding(name), _) => Ok(name),
+      (token, at) => Err(self.unexpected(token, at)),
     }
   }
 }
@@ -138,7 +788,7 @@ mod tests {
       let mut parser = Parser::new(lexer);
 
       let ast = parser.parse();
-      assert_eq!(ast, Ok(Term::Variable("x")));
+      assert_eq!(ast, Ok(Term::Variable("x".into())));
     }
 
     #[test]
@@ -151,8 +801,8 @@ mod tests {
       assert_eq!(
         ast,
         Ok(Term::Abstraction {
-          param: "x",
-          body: Box::new(Term::Variable("x"))
+          param: "x".into(),
+          body: Rc::new(Term::Variable("x".into()))
         })
       );
     }
@@ -167,10 +817,10 @@ mod tests {
       assert_eq!(
         ast,
         Ok(Term::Abstraction {
-          param: "x",
-          body: Box::new(Term::Abstraction {
-            param: "y",
-            body: Box::new(Term::Variable("x"))
+          param: "x".into(),
+          body: Rc::new(Term::Abstraction {
+            param: "y".into(),
+            body: Rc::new(Term::Variable("x".into()))
           })
         })
       );
@@ -186,8 +836,8 @@ mod tests {
       assert_eq!(
         ast,
         Ok(Term::Application {
-          lhs: Box::new(Term::Variable("x")),
-          rhs: Box::new(Term::Variable("y"))
+          lhs: Rc::new(Term::Variable("x".into())),
+          rhs: Rc::new(Term::Variable("y".into()))
         })
       );
     }
@@ -202,11 +852,11 @@ mod tests {
       assert_eq!(
         ast,
         Ok(Term::Application {
-          lhs: Box::new(Term::Application {
-            lhs: Box::new(Term::Variable("x")),
-            rhs: Box::new(Term::Variable("y")),
+          lhs: Rc::new(Term::Application {
+            lhs: Rc::new(Term::Variable("x".into())),
+            rhs: Rc::new(Term::Variable("y".into())),
           }),
-          rhs: Box::new(Term::Variable("z"))
+          rhs: Rc::new(Term::Variable("z".into()))
         })
       );
     }
@@ -221,11 +871,11 @@ mod tests {
       assert_eq!(
         ast,
         Ok(Term::Application {
-          lhs: Box::new(Term::Abstraction {
-            param: "x",
-            body: Box::new(Term::Variable("x"))
+          lhs: Rc::new(Term::Abstraction {
+            param: "x".into(),
+            body: Rc::new(Term::Variable("x".into()))
           }),
-          rhs: Box::new(Term::Variable("y"))
+          rhs: Rc::new(Term::Variable("y".into()))
         })
       );
     }
@@ -240,10 +890,10 @@ mod tests {
       assert_eq!(
         ast,
         Ok(Term::Application {
-          lhs: Box::new(Term::Variable("x")),
-          rhs: Box::new(Term::Application {
-            lhs: Box::new(Term::Variable("y")),
-            rhs: Box::new(Term::Variable("z"))
+          lhs: Rc::new(Term::Variable("x".into())),
+          rhs: Rc::new(Term::Application {
+            lhs: Rc::new(Term::Variable("y".into())),
+            rhs: Rc::new(Term::Variable("z".into()))
           })
         })
       );
@@ -259,16 +909,992 @@ mod tests {
       assert_eq!(
         ast,
         Ok(Term::Abstraction {
-          param: "x",
-          body: Box::new(Term::Application {
-            lhs: Box::new(Term::Variable("x")),
-            rhs: Box::new(Term::Abstraction {
-              param: "y",
-              body: Box::new(Term::Variable("y"))
+          param: "x".into(),
+          body: Rc::new(Term::Application {
+            lhs: Rc::new(Term::Variable("x".into())),
+            rhs: Rc::new(Term::Abstraction {
+              param: "y".into(),
+              body: Rc::new(Term::Variable("y".into()))
             })
           })
         })
       );
     }
+
+    #[test]
+    fn parse_abstraction_arrow_separator() {
+      let arrow = Parser::new(Lexer::new("\\x -> x")).parse();
+      let dot = Parser::new(Lexer::new("\\x . x")).parse();
+      assert_eq!(arrow, dot);
+      assert_eq!(
+        arrow,
+        Ok(Term::Abstraction {
+          param: "x".into(),
+          body: Rc::new(Term::Variable("x".into()))
+        })
+      );
+    }
+
+    #[test]
+    fn parse_abstraction_several_params() {
+      let nested = Parser::new(Lexer::new("λx. λy. λz. x z (y z)")).parse();
+      for input in [
+        "λx, y, z. x z (y z)",
+        "λx,y,z. x z (y z)",
+        "λx y z. x z (y z)",
+        "λx, y z -> x z (y z)",
+      ] {
+        assert_eq!(Parser::new(Lexer::new(input)).parse(), nested, "{input}");
+      }
+    }
+
+    #[test]
+    fn parse_abstraction_dangling_comma() {
+      assert_eq!(
+        Parser::new(Lexer::new("λx, . x")).parse(),
+        Err(ParseError::UnexpectedToken {
+          token: Token::Dot,
+          at: 3
+        })
+      );
+      assert_eq!(
+        Parser::new(Lexer::new("λx,")).parse(),
+        Err(ParseError::UnexpectedEof {
+          expected: Some("an identifier")
+        })
+      );
+      assert_eq!(
+        Parser::new(Lexer::new("λ, x. x")).parse(),
+        Err(ParseError::UnexpectedToken {
+          token: Token::Comma,
+          at: 1
+        })
+      );
+    }
+
+    #[test]
+    fn parse_number_literals() {
+      assert_eq!(Parser::new(Lexer::new("2")).parse(), Ok(numeral(2)));
+      assert_eq!(
+        Parser::new(Lexer::new("f 0 (g 3)")).parse(),
+        Ok(Term::from_spine(
+          Term::var("f"),
+          [numeral(0), Term::app(Term::var("g"), numeral(3))]
+        ))
+      );
+      // a rendered numeral reads back as the same term
+      let term = Term::app(Term::var("f"), numeral(4));
+      let rendered = term.display_numerals().to_string();
+      assert_eq!(Parser::new(Lexer::new(&rendered)).parse(), Ok(term));
+    }
+
+    #[test]
+    fn parse_abstraction_optional_separator() {
+      let lenient = |input| {
+        Parser::new(Lexer::new(input))
+          .with_optional_separator()
+          .parse()
+      };
+      assert_eq!(lenient("λx x"), Ok(Term::abs("x", Term::var("x"))));
+      assert_eq!(lenient("λx x"), lenient("λx. x"));
+      assert_eq!(
+        lenient("λx y z"),
+        Parser::new(Lexer::new("λx. y z")).parse()
+      );
+      assert_eq!(
+        lenient("λf f (g f)"),
+        Parser::new(Lexer::new("λf. f (g f)")).parse()
+      );
+      assert_eq!(
+        lenient("λf λx f x"),
+        Parser::new(Lexer::new("λf. λx. f x")).parse()
+      );
+      // separators still work, and several binders still need one
+      assert_eq!(
+        lenient("λx y. x"),
+        Parser::new(Lexer::new("λx. λy. x")).parse()
+      );
+      assert_eq!(
+        lenient("λx, y x"),
+        Err(ParseError::UnexpectedEof {
+          expected: Some("`.` or `->`")
+        })
+      );
+      assert_eq!(
+        lenient("λx"),
+        Err(ParseError::MissingAbstractionBody { at: 0 })
+      );
+    }
+
+    #[test]
+    fn parse_application_associativity() {
+      let parse = |input, associativity| {
+        Parser::new(Lexer::new(input))
+          .with_associativity(associativity)
+          .parse()
+          .unwrap()
+      };
+      let (x, y, z) = (Term::var("x"), Term::var("y"), Term::var("z"));
+      assert_eq!(
+        parse("x y z", Associativity::Left),
+        Term::app(Term::app(x.clone(), y.clone()), z.clone())
+      );
+      assert_eq!(
+        parse("x y z", Associativity::Right),
+        Term::app(x.clone(), Term::app(y.clone(), z.clone()))
+      );
+      // parentheses still group explicitly
+      assert_eq!(
+        parse("(x y) z", Associativity::Right),
+        parse("x y z", Associativity::Left)
+      );
+      assert_eq!(
+        parse("λf. f x y", Associativity::Right),
+        Term::abs(
+          "f",
+          Term::app(Term::var("f"), Term::app(x.clone(), y.clone()))
+        )
+      );
+      let combinatory = Parser::new(Lexer::new("s k k"))
+        .with_associativity(Associativity::Right)
+        .parse_combinatory();
+      assert_eq!(
+        combinatory,
+        Ok(Term::app(
+          Term::var("s"),
+          Term::app(Term::var("k"), Term::var("k"))
+        ))
+      );
+      let lenient = Parser::new(Lexer::new("λf x y"))
+        .with_optional_separator()
+        .with_associativity(Associativity::Right)
+        .parse();
+      assert_eq!(lenient, Ok(Term::abs("f", Term::app(x, y))));
+    }
+
+    #[test]
+    fn parse_abstraction_body_scope() {
+      let parse = |input, body_scope| {
+        Parser::new(Lexer::new(input))
+          .with_body_scope(body_scope)
+          .parse()
+          .unwrap()
+      };
+      let (a, b) = (Term::var("a"), Term::var("b"));
+      assert_eq!(
+        parse("λx. a b", BodyScope::Maximal),
+        Term::abs("x", Term::app(a.clone(), b.clone()))
+      );
+      assert_eq!(
+        parse("λx. a b", BodyScope::Atom),
+        Term::app(Term::abs("x", a.clone()), b.clone())
+      );
+      // parentheses still extend the body
+      assert_eq!(
+        parse("λx. (a b)", BodyScope::Atom),
+        parse("λx. a b", BodyScope::Maximal)
+      );
+      // an abstraction is an atom, so nested ones still nest
+      assert_eq!(
+        parse("λx. λy. a b", BodyScope::Atom),
+        Term::app(Term::abs("x", Term::abs("y", a.clone())), b.clone())
+      );
+      assert_eq!(
+        parse("λx, y. a b", BodyScope::Atom),
+        parse("λx. λy. a b", BodyScope::Atom)
+      );
+      let lenient = Parser::new(Lexer::new("λx a b"))
+        .with_optional_separator()
+        .with_body_scope(BodyScope::Atom)
+        .parse();
+      assert_eq!(lenient, Ok(Term::app(Term::abs("x", a), b)));
+    }
+
+    #[test]
+    fn parse_abstraction_separator_required_by_default() {
+      assert_eq!(
+        Parser::new(Lexer::new("λx x")).parse(),
+        Err(ParseError::UnexpectedEof {
+          expected: Some("`.` or `->`")
+        })
+      );
+    }
+
+    #[test]
+    fn parse_let_in() {
+      let parse = |input| Parser::new(Lexer::new(input)).parse();
+      assert_eq!(
+        parse("let id = λx. x in id y"),
+        parse("(λid. id y) (λx. x)")
+      );
+      assert_eq!(
+        parse("let a = b in let c = a in c"),
+        parse("(λa. (λc. c) a) b")
+      );
+      // escaped, the keywords are ordinary names
+      assert_eq!(
+        parse("let `in` = a in `in`"),
+        parse("(λx. x) a").map(|term| { term.rename(|name| (name == "x").then_some("in")) })
+      );
+      assert_eq!(
+        parse("let x = a"),
+        Err(ParseError::UnexpectedEof {
+          expected: Some("`in`")
+        })
+      );
+      assert_eq!(
+        parse("let in = a in a"),
+        Err(ParseError::UnexpectedToken {
+          token: Token::In,
+          at: 1
+        })
+      );
+    }
+
+    #[test]
+    fn parse_abstraction_arrow_nested() {
+      let arrow = Parser::new(Lexer::new("\\x -> \\y -> x y")).parse();
+      let dot = Parser::new(Lexer::new("\\x. \\y. x y")).parse();
+      assert_eq!(arrow, dot);
+    }
+
+    #[test]
+    fn peek_doesnt_consume() {
+      let mut parser = Parser::new(Lexer::new("x y"));
+      assert_eq!(parser.peek(), Some(&Token::Binding("x")));
+      assert_eq!(parser.peek(), Some(&Token::Binding("x")));
+      assert!(!parser.is_eof());
+      assert_eq!(
+        parser.parse(),
+        Ok(Term::app(Term::var("x"), Term::var("y")))
+      );
+      assert_eq!(parser.peek(), None);
+      assert!(parser.is_eof());
+      assert!(Parser::new(Lexer::new("  ")).is_eof());
+    }
+
+    #[test]
+    fn parse_from_tokens() {
+      let tokens = vec![
+        Token::Lambda('λ'),
+        Token::Binding("x"),
+        Token::Dot,
+        Token::Binding("x"),
+      ];
+      let mut parser = Parser::from_tokens(tokens);
+
+      let ast = parser.parse();
+      assert_eq!(ast, Ok(Term::abs("x", Term::var("x"))));
+    }
+
+    #[test]
+    fn parse_until_eof_token() {
+      let mut parser = Parser::from_tokens(vec![Token::Binding("x"), Token::Eof]);
+      assert_eq!(parser.parse(), Ok(Term::var("x")));
+      // nothing after it is read
+      let tokens = [Token::Binding("x"), Token::Eof, Token::RParen];
+      let mut parser = Parser::from_slice(&tokens);
+      assert_eq!(parser.parse(), Ok(Term::var("x")));
+      let parser = Parser::from_tokens(vec![Token::Eof]);
+      assert!(parser.is_eof());
+      let mut parser = Parser::from_tokens(vec![Token::LParen, Token::Binding("x"), Token::Eof]);
+      assert_eq!(
+        parser.parse(),
+        Err(ParseError::UnclosedParen { opened_at: 0 })
+      );
+    }
+
+    #[test]
+    fn parse_skips_trivia() {
+      let mut parser = Parser::new(Lexer::new("x -- c\n y").with_trivia());
+      assert_eq!(
+        parser.parse(),
+        Ok(Term::app(Term::var("x"), Term::var("y")))
+      );
+      let parser = Parser::new(Lexer::new(" -- only a comment").with_trivia());
+      assert!(parser.is_eof());
+    }
+
+    #[test]
+    fn parse_from_slice() {
+      let tokens = [Token::Binding("f"), Token::Binding("x")];
+      let mut parser = Parser::from_slice(&tokens);
+
+      let ast = parser.parse();
+      assert_eq!(ast, Ok(Term::app(Term::var("f"), Term::var("x"))));
+      // the tokens are still ours afterwards
+      assert_eq!(tokens.len(), 2);
+    }
+
+    #[test]
+    fn parse_from_slice_error() {
+      let tokens = [Token::Lambda('λ'), Token::Dot];
+      let mut parser = Parser::from_slice(&tokens);
+
+      let ast = parser.parse();
+      assert_eq!(
+        ast,
+        Err(ParseError::UnexpectedToken {
+          token: Token::Dot,
+          at: 1
+        })
+      );
+    }
+
+    #[test]
+    fn parse_program_definitions() {
+      let input = "id = \\x.x; const = \\x.\\y.x; const id";
+      let mut parser = Parser::new(Lexer::new(input));
+
+      let program = parser.parse_program().unwrap();
+      let id = Term::abs("x", Term::var("x"));
+      let constant = Term::abs("x", Term::abs("y", Term::var("x")));
+      assert_eq!(program.definition("id"), Some(&id));
+      assert_eq!(program.definition("const"), Some(&constant));
+      assert_eq!(program.body, Term::app(constant, id));
+    }
+
+    #[test]
+    fn parse_program_evaluates() {
+      use crate::interpreter::Interpreter;
+      use crate::term::Evaluate;
+
+      let input = "id = \\x.x; const = \\x.\\y.x; const id";
+      let program = Parser::new(Lexer::new(input)).parse_program().unwrap();
+      let result = Interpreter::new().evaluate(&program.body);
+      // λy. λx. x
+      assert_eq!(result, Term::abs("y", Term::abs("x", Term::var("x"))));
+    }
+
+    #[test]
+    fn parse_program_inlines_earlier_definitions() {
+      let input = "id = \\x.x; twice = \\f.\\x.f (f x); twice id;";
+      let program = Parser::new(Lexer::new(input)).parse_program().unwrap();
+      let id = Term::abs("x", Term::var("x"));
+      let twice = Term::abs(
+        "f",
+        Term::abs(
+          "x",
+          Term::app(Term::var("f"), Term::app(Term::var("f"), Term::var("x"))),
+        ),
+      );
+      assert_eq!(program.body, Term::app(twice, id));
+    }
+
+    #[test]
+    fn parse_program_expression_only() {
+      let program = Parser::new(Lexer::new("f x")).parse_program().unwrap();
+      assert!(program.definitions.is_empty());
+      assert_eq!(program.body, Term::app(Term::var("f"), Term::var("x")));
+    }
+
+    #[test]
+    fn parse_program_use_before_definition() {
+      let input = "k = \\x.id; id = \\x.x; k";
+      let mut parser = Parser::new(Lexer::new(input));
+      assert_eq!(
+        parser.parse_program(),
+        Err(ParseError::UsedBeforeDefinition("id"))
+      );
+
+      // definitions can't refer to themselves either
+      let mut parser = Parser::new(Lexer::new("loop = loop; loop"));
+      assert_eq!(
+        parser.parse_program(),
+        Err(ParseError::UsedBeforeDefinition("loop"))
+      );
+    }
+
+    #[test]
+    fn parse_sequence() {
+      let mut parser = Parser::new(Lexer::new("x; y; z"));
+      assert_eq!(
+        parser.parse_sequence(),
+        Ok(vec![Term::var("x"), Term::var("y"), Term::var("z")])
+      );
+      let mut parser = Parser::new(Lexer::new("x;"));
+      assert_eq!(parser.parse_sequence(), Ok(vec![Term::var("x")]));
+      let mut parser = Parser::new(Lexer::new("(λx. x) y;\nλz. z;"));
+      assert_eq!(
+        parser.parse_sequence(),
+        Ok(vec![
+          Term::app(Term::abs("x", Term::var("x")), Term::var("y")),
+          Term::abs("z", Term::var("z")),
+        ])
+      );
+      let mut parser = Parser::new(Lexer::new(""));
+      assert_eq!(parser.parse_sequence(), Ok(vec![]));
+    }
+
+    #[test]
+    fn parse_program_resilient_recovers() {
+      let mut parser = Parser::new(Lexer::new("id = λx. x; id a; λ. b; id c"));
+      let (terms, errors) = parser.parse_program_resilient();
+      let id = Term::abs("x", Term::var("x"));
+      assert_eq!(
+        terms,
+        vec![
+          Term::app(id.clone(), Term::var("a")),
+          Term::app(id, Term::var("c"))
+        ]
+      );
+      assert_eq!(
+        errors,
+        vec![ParseError::UnexpectedToken {
+          token: Token::Dot,
+          at: 11
+        }]
+      );
+    }
+
+    #[test]
+    fn parse_program_resilient_semicolon_consumed() {
+      // the `;` is what ends up unexpected, so nothing more should be skipped
+      let mut parser = Parser::new(Lexer::new("(x; y; (z"));
+      let (terms, errors) = parser.parse_program_resilient();
+      assert_eq!(terms, vec![Term::var("y")]);
+      assert_eq!(
+        errors,
+        vec![
+          ParseError::UnexpectedToken {
+            token: Token::Semicolon,
+            at: 2
+          },
+          ParseError::UnclosedParen { opened_at: 5 },
+        ]
+      );
+    }
+
+    #[test]
+    fn parse_program_resilient_line_break() {
+      let input = "a = λ. x\nb;\nc = ) y\nd; e";
+      let resilient = |lexer: Lexer<'static>| Parser::new(lexer).parse_program_resilient();
+      let (terms, errors) = resilient(Lexer::new(input).with_trivia());
+      assert_eq!(terms, vec![Term::var("b"), Term::var("d"), Term::var("e")]);
+      assert_eq!(
+        errors,
+        vec![
+          ParseError::UnexpectedToken {
+            token: Token::Dot,
+            at: 3
+          },
+          ParseError::UnmatchedCloseParen { at: 9 },
+        ]
+      );
+      // without trivia there are no line breaks, so each broken line swallows
+      // the next statement
+      let (terms, errors) = resilient(Lexer::new(input));
+      assert_eq!(terms, vec![Term::var("e")]);
+      assert_eq!(errors.len(), 2);
+      // an error right at the start of a line still skips past it
+      let (terms, errors) = resilient(Lexer::new("a;\n. b\nc").with_trivia());
+      assert_eq!(terms, vec![Term::var("a"), Term::var("c")]);
+      assert_eq!(errors.len(), 1);
+    }
+
+    #[test]
+    fn parse_program_resilient_semicolon_left() {
+      // the `;` is unexpected where an atom should be, but still has to be skipped
+      let semicolon = |at| ParseError::UnexpectedToken {
+        token: Token::Semicolon,
+        at,
+      };
+      for (input, terms, at) in [
+        ("x;; y", vec![Term::var("x"), Term::var("y")], 2),
+        ("; x", vec![Term::var("x")], 0),
+        ("a = ; b", vec![Term::var("b")], 2),
+      ] {
+        let mut parser = Parser::new(Lexer::new(input));
+        assert_eq!(
+          parser.parse_program_resilient(),
+          (terms, vec![semicolon(at)]),
+          "{input}"
+        );
+      }
+    }
+
+    #[test]
+    fn parse_sequence_errors() {
+      // a separator needs a term on both sides
+      let mut parser = Parser::new(Lexer::new("x;; y"));
+      assert_eq!(
+        parser.parse_sequence(),
+        Err(ParseError::UnexpectedToken {
+          token: Token::Semicolon,
+          at: 2
+        })
+      );
+      let mut parser = Parser::new(Lexer::new("x ) y"));
+      assert_eq!(
+        parser.parse_sequence(),
+        Err(ParseError::UnmatchedCloseParen { at: 1 })
+      );
+    }
+
+    #[test]
+    fn parse_one_atom() {
+      let mut parser = Parser::new(Lexer::new("x y"));
+      assert_eq!(parser.parse_one_atom(), Ok(Term::var("x")));
+      assert_eq!(parser.peek(), Some(&Token::Binding("y")));
+      assert_eq!(
+        parser.into_remaining().collect::<Vec<_>>(),
+        [Token::Binding("y")]
+      );
+
+      let mut parser = Parser::new(Lexer::new("(f x) λy. y y ; rest"));
+      assert_eq!(
+        parser.parse_one_atom(),
+        Ok(Term::app(Term::var("f"), Term::var("x")))
+      );
+      // the abstraction's body runs up to where the term ends
+      assert_eq!(
+        parser.parse_one_atom(),
+        Ok(Term::abs("y", Term::app(Term::var("y"), Term::var("y"))))
+      );
+      assert_eq!(
+        parser.into_remaining().collect::<Vec<_>>(),
+        [Token::Semicolon, Token::Binding("rest")]
+      );
+
+      let mut parser = Parser::new(Lexer::new(". x"));
+      assert_eq!(
+        parser.parse_one_atom(),
+        Err(ParseError::UnexpectedToken {
+          token: Token::Dot,
+          at: 0
+        })
+      );
+    }
+
+    #[test]
+    fn parse_program_missing_body() {
+      let mut parser = Parser::new(Lexer::new("id = \\x.x;"));
+      assert_eq!(
+        parser.parse_program(),
+        Err(ParseError::UnexpectedEof {
+          expected: Some("a term")
+        })
+      );
+    }
+
+    #[test]
+    fn parse_program_defines_non_name() {
+      let mut parser = Parser::new(Lexer::new("f x = x; f"));
+      assert_eq!(
+        parser.parse_program(),
+        Err(ParseError::UnexpectedToken {
+          token: Token::Equals,
+          at: 2
+        })
+      );
+    }
+
+    #[test]
+    fn display_parse_error() {
+      assert_eq!(
+        ParseError::UnexpectedEof { expected: None }.to_string(),
+        "unexpected end of input"
+      );
+      assert_eq!(
+        ParseError::UnexpectedEof {
+          expected: Some("`)`")
+        }
+        .to_string(),
+        "unexpected end of input, expected `)`"
+      );
+      assert_eq!(
+        ParseError::UnexpectedToken {
+          token: Token::RParen,
+          at: 0
+        }
+        .to_string(),
+        "unexpected token `)`"
+      );
+      assert_eq!(
+        ParseError::UnexpectedToken {
+          token: Token::Lambda('λ'),
+          at: 0
+        }
+        .to_string(),
+        "unexpected token `λ`"
+      );
+      assert_eq!(
+        ParseError::UnexpectedToken {
+          token: Token::Binding("foo"),
+          at: 0
+        }
+        .to_string(),
+        "unexpected token `foo`"
+      );
+      assert_eq!(
+        ParseError::UnexpectedToken {
+          token: Token::Invalid('\0'),
+          at: 0
+        }
+        .to_string(),
+        "invalid character `\\0`"
+      );
+      assert_eq!(
+        ParseError::UsedBeforeDefinition("id").to_string(),
+        "`id` was used before it was defined"
+      );
+      assert_eq!(
+        ParseError::NestingTooDeep { at: 0 }.to_string(),
+        "terms nested too deeply"
+      );
+    }
+
+    #[test]
+    fn unclosed_paren() {
+      let mut parser = Parser::new(Lexer::new("(x"));
+      assert_eq!(
+        parser.parse(),
+        Err(ParseError::UnclosedParen { opened_at: 0 })
+      );
+      // the inner pair is fine, it's the outer one left open
+      let mut parser = Parser::new(Lexer::new("((x)"));
+      assert_eq!(
+        parser.parse(),
+        Err(ParseError::UnclosedParen { opened_at: 0 })
+      );
+      let mut parser = Parser::new(Lexer::new("x ((y) (z"));
+      assert_eq!(
+        parser.parse(),
+        Err(ParseError::UnclosedParen { opened_at: 5 })
+      );
+    }
+
+    #[test]
+    fn unclosed_paren_missing_more_than_paren() {
+      // the body is missing too, so closing the paren wouldn't be enough
+      let mut parser = Parser::new(Lexer::new("(λx."));
+      assert_eq!(
+        parser.parse(),
+        Err(ParseError::MissingAbstractionBody { at: 1 })
+      );
+    }
+
+    #[test]
+    fn missing_abstraction_body() {
+      for input in ["λx.", "\\x.", "λx ->", "λx y. "] {
+        assert_eq!(
+          Parser::new(Lexer::new(input)).parse(),
+          Err(ParseError::MissingAbstractionBody { at: 0 }),
+          "{input}"
+        );
+      }
+      // ended by the enclosing term instead
+      assert_eq!(
+        Parser::new(Lexer::new("f (λx.)")).parse(),
+        Err(ParseError::MissingAbstractionBody { at: 2 })
+      );
+      let input = "id = λx.; id";
+      let error = Parser::new(Lexer::new(input)).parse_program().unwrap_err();
+      assert_eq!(error, ParseError::MissingAbstractionBody { at: 2 });
+      assert_eq!(
+        error.render(input),
+        "abstraction is missing a body\n --> 1:6\n  |\n1 | id = λx.; id\n  |      ^"
+      );
+    }
+
+    #[test]
+    fn nesting_too_deep() {
+      let input = "(".repeat(1_000_000);
+      assert_eq!(
+        Parser::new(Lexer::new(&input))
+          .with_max_nesting(256)
+          .parse(),
+        Err(ParseError::NestingTooDeep { at: 256 })
+      );
+      let input = "λx. ".repeat(100_000);
+      assert_eq!(
+        Parser::new(Lexer::new(&input))
+          .with_max_nesting(256)
+          .parse(),
+        Err(ParseError::NestingTooDeep { at: 3 * 256 })
+      );
+      let input = "s (k (s i)) k";
+      assert_eq!(
+        Parser::new(Lexer::new(input))
+          .with_max_nesting(1)
+          .parse_combinatory(),
+        Err(ParseError::NestingTooDeep { at: 3 })
+      );
+    }
+
+    #[test]
+    fn nesting_within_limit() {
+      let input = format!("{}x{}", "(".repeat(10), ")".repeat(10));
+      let mut parser = Parser::new(Lexer::new(&input)).with_max_nesting(10);
+      assert_eq!(parser.parse(), Ok(Term::var("x")));
+      let mut parser = Parser::new(Lexer::new("(λx. (x)) ((y))")).with_max_nesting(3);
+      assert!(parser.parse().is_ok());
+      // each statement starts from the top again
+      let input = "((x)); (y); ((z))";
+      let (terms, errors) = Parser::new(Lexer::new(input))
+        .with_max_nesting(1)
+        .parse_program_resilient();
+      assert_eq!(terms, [Term::var("y")]);
+      assert_eq!(errors.len(), 2);
+    }
+
+    #[test]
+    fn nesting_unlimited_by_default() {
+      // a numeral displays nested once per application of `f`
+      let numeral = numeral(300);
+      let input = numeral.to_string();
+      assert_eq!(Parser::new(Lexer::new(&input)).parse(), Ok(numeral.clone()));
+      assert_eq!(crate::api::parse_str(&input), Ok(numeral));
+    }
+
+    #[test]
+    fn parse_either_lambda_glyph() {
+      let backslash = Parser::new(Lexer::new("\\x.x")).parse();
+      let lambda = Parser::new(Lexer::new("λx.x")).parse();
+      assert_eq!(backslash, Ok(Term::abs("x", Term::var("x"))));
+      assert_eq!(backslash, lambda);
+    }
+
+    #[test]
+    fn parse_invalid_character() {
+      let mut parser = Parser::new(Lexer::new("λx. x ? y"));
+      assert_eq!(
+        parser.parse(),
+        Err(ParseError::UnexpectedToken {
+          token: Token::Invalid('?'),
+          at: 4
+        })
+      );
+    }
+
+    #[test]
+    fn unexpected_eof_expected() {
+      let expected = |input| match Parser::new(Lexer::new(input)).parse() {
+        Err(ParseError::UnexpectedEof { expected }) => expected,
+        other => panic!("expected an unexpected EOF, got {other:?}"),
+      };
+      assert_eq!(expected("λ"), Some("an identifier"));
+      assert_eq!(expected("λx"), Some("`.` or `->`"));
+      assert_eq!(expected("λx -> ("), Some("a term"));
+      assert_eq!(expected(""), Some("a term"));
+    }
+
+    #[test]
+    fn unexpected_eof_closing_paren() {
+      // a missing `)` gets its own variant, which says where the `(` was
+      let error = Parser::new(Lexer::new("(x")).parse().unwrap_err();
+      assert_eq!(error, ParseError::UnclosedParen { opened_at: 0 });
+      assert_eq!(error.to_string(), "unclosed `(`, expected `)`");
+
+      // but asking for one directly reports it as what was expected
+      let tokens = [Token::LParen];
+      let mut parser = Parser::from_slice(&tokens);
+      assert_eq!(
+        parser
+          .eat(Token::LParen)
+          .and_then(|()| parser.eat(Token::RParen)),
+        Err(ParseError::UnexpectedEof {
+          expected: Some("`)`")
+        })
+      );
+    }
+
+    #[test]
+    fn parse_error_position() {
+      let mut parser = Parser::new(Lexer::new("λx. x ="));
+      assert_eq!(
+        parser.parse(),
+        Err(ParseError::UnexpectedToken {
+          token: Token::Equals,
+          at: 4
+        })
+      );
+    }
+
+    #[test]
+    fn render_unexpected_token() {
+      let input = "x =";
+      let error = Parser::new(Lexer::new(input)).parse().unwrap_err();
+      assert_eq!(
+        error.render(input),
+        "unexpected token `=`\n --> 1:3\n  |\n1 | x =\n  |   ^"
+      );
+    }
+
+    #[test]
+    fn unmatched_close_paren() {
+      for (input, at) in [(")", 0), ("x )", 1), (")x", 0), ("(x))", 3)] {
+        assert_eq!(
+          Parser::new(Lexer::new(input)).parse(),
+          Err(ParseError::UnmatchedCloseParen { at }),
+          "{input}"
+        );
+      }
+      let error = Parser::new(Lexer::new("s k)")).parse_combinatory();
+      assert_eq!(error, Err(ParseError::UnmatchedCloseParen { at: 2 }));
+      // closing something, just too early
+      assert_eq!(
+        Parser::new(Lexer::new("(λx. x.)")).parse(),
+        Err(ParseError::UnexpectedToken {
+          token: Token::Dot,
+          at: 5
+        })
+      );
+      assert_eq!(
+        Parser::new(Lexer::new("()")).parse(),
+        Err(ParseError::UnexpectedToken {
+          token: Token::RParen,
+          at: 1
+        })
+      );
+      let input = "x )";
+      let error = Parser::new(Lexer::new(input)).parse().unwrap_err();
+      assert_eq!(
+        error.render(input),
+        "unmatched `)`\n --> 1:3\n  |\n1 | x )\n  |   ^"
+      );
+    }
+
+    #[test]
+    fn render_multi_byte_and_multi_line() {
+      // 'λ' is two bytes but one column
+      let input = "id = λx. x;\nλy. y y y =";
+      let error = Parser::new(Lexer::new(input)).parse_program().unwrap_err();
+      assert_eq!(
+        error.render(input),
+        "unexpected token `=`\n --> 2:11\n  |\n2 | λy. y y y =\n  |           ^"
+      );
+    }
+
+    #[test]
+    fn render_wide_token() {
+      let input = "λ(x";
+      let error = Parser::new(Lexer::new(input)).parse().unwrap_err();
+      assert_eq!(
+        error.render(input),
+        "unexpected token `(`\n --> 1:2\n  |\n1 | λ(x\n  |  ^"
+      );
+      let input = "λfoo -> ->";
+      let error = Parser::new(Lexer::new(input)).parse().unwrap_err();
+      assert_eq!(
+        error.render(input),
+        "unexpected token `->`\n --> 1:9\n  |\n1 | λfoo -> ->\n  |         ^^"
+      );
+    }
+
+    #[test]
+    fn render_unexpected_eof() {
+      let input = "(λx. (  \n";
+      let error = Parser::new(Lexer::new(input)).parse().unwrap_err();
+      assert_eq!(
+        error.render(input),
+        "unexpected end of input, expected a term\n --> 1:7\n  |\n1 | (λx. (  \n  |       ^"
+      );
+    }
+
+    #[test]
+    fn render_unclosed_paren() {
+      let input = "x (λx. x";
+      let error = Parser::new(Lexer::new(input)).parse().unwrap_err();
+      assert_eq!(
+        error.render(input),
+        "unclosed `(`, expected `)`\n --> 1:3\n  |\n1 | x (λx. x\n  |   ^"
+      );
+    }
+
+    #[test]
+    fn parse_combinatory_accepts_applications() {
+      let mut parser = Parser::new(Lexer::new("s k k"));
+      assert_eq!(
+        parser.parse_combinatory(),
+        Ok(Term::from_spine(
+          Term::var("s"),
+          [Term::var("k"), Term::var("k")]
+        ))
+      );
+
+      let mut parser = Parser::new(Lexer::new("s (k s) k"));
+      assert_eq!(
+        parser.parse_combinatory(),
+        Parser::new(Lexer::new("s (k s) k")).parse()
+      );
+    }
+
+    #[test]
+    fn parse_combinatory_rejects_abstractions() {
+      let mut parser = Parser::new(Lexer::new("\\x.x"));
+      assert_eq!(
+        parser.parse_combinatory(),
+        Err(ParseError::UnexpectedToken {
+          token: Token::Lambda('\\'),
+          at: 0
+        })
+      );
+
+      let mut parser = Parser::new(Lexer::new("s (λx. x)"));
+      assert_eq!(
+        parser.parse_combinatory(),
+        Err(ParseError::UnexpectedToken {
+          token: Token::Lambda('λ'),
+          at: 2
+        })
+      );
+
+      let mut parser = Parser::new(Lexer::new("s ("));
+      assert_eq!(
+        parser.parse_combinatory(),
+        Err(ParseError::UnexpectedEof {
+          expected: Some("a variable or `(`")
+        })
+      );
+    }
+  }
+
+  mod no_panic {
+    use super::*;
+
+    use crate::rng::Rng;
+
+    /// Mostly characters the grammar cares about, so inputs get past the lexer
+    /// often enough to exercise the parser, plus a few it doesn't
+    const ALPHABET: &[char] = &[
+      'λ', '\\', '.', '-', '>', '(', ')', '=', ';', ',', ' ', '\n', '\t', 'x', 'y', 'f', '0', 'α',
+      'X', '?', '\0', '\u{c}', '\u{a0}', '😀',
+    ];
+
+    fn random_input(rng: &mut Rng) -> String {
+      let len = rng.below(48);
+      (0..len)
+        .map(|_| ALPHABET[rng.below(ALPHABET.len())])
+        .collect()
+    }
+
+    #[test]
+    fn random_input_never_panics() {
+      let mut rng = Rng(0x5eed_1234_abcd_ef01);
+      for _ in 0..20_000 {
+        let input = random_input(&mut rng);
+        // any of these panicking fails the test, which is all we're checking
+        if let Err(e) = Parser::new(Lexer::new(&input)).parse() {
+          let _ = e.render(&input);
+        }
+        if let Err(e) = Parser::new(Lexer::new(&input)).parse_program() {
+          let _ = e.render(&input);
+        }
+      }
+    }
+
+    #[test]
+    fn random_input_sometimes_parses() {
+      // make sure the generator isn't so noisy that only the lexer gets tested
+      let mut rng = Rng(0x5eed_1234_abcd_ef01);
+      let parsed = (0..20_000)
+        .filter(|_| {
+          let input = random_input(&mut rng);
+          Parser::new(Lexer::new(&input)).parse().is_ok()
+        })
+        .count();
+      assert!(parsed > 100, "only {parsed} inputs parsed");
+    }
   }
 }
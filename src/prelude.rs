@@ -0,0 +1,231 @@
+//!
+//! Ready-made terms for common combinators, Church booleans, and Church numerals
+//!
+//! Apply them to each other with `Term::app`/`Term::apps` and evaluate as usual;
+//! most arithmetic only reaches a recognizable numeral under
+//! `Interpreter::with_full_normalization`
+//!
+
+use crate::term::Term;
+
+/// Identity, `λx. x`
+pub fn i() -> Term<'static> {
+  Term::abs("x", Term::var("x"))
+}
+
+/// Constant, `λx. λy. x`
+pub fn k() -> Term<'static> {
+  Term::abs("x", Term::abs("y", Term::var("x")))
+}
+
+/// Substitution, `λx. λy. λz. x z (y z)`
+pub fn s() -> Term<'static> {
+  Term::abs(
+    "x",
+    Term::abs(
+      "y",
+      Term::abs(
+        "z",
+        Term::app(
+          Term::app(Term::var("x"), Term::var("z")),
+          Term::app(Term::var("y"), Term::var("z")),
+        ),
+      ),
+    ),
+  )
+}
+
+/// Fixed-point combinator, `λf. (λx. f (x x)) (λx. f (x x))`, for recursion
+/// - `y g` reduces to `g (y g)` and so on forever unless `g` stops using its
+///   argument, so evaluate it under a step or recursion limit
+pub fn y() -> Term<'static> {
+  let half = Term::abs(
+    "x",
+    Term::app(Term::var("f"), Term::app(Term::var("x"), Term::var("x"))),
+  );
+  Term::abs("f", Term::app(half.clone(), half))
+}
+
+/// Church encoding of `n`, `λf. λx. f (f ... x)` with `n` applications of `f`
+pub fn numeral(n: u64) -> Term<'static> {
+  let body = (0..n).fold(Term::var("x"), |acc, _| Term::app(Term::var("f"), acc));
+  Term::abs("f", Term::abs("x", body))
+}
+
+/// Successor, `λn. λf. λx. f (n f x)`
+pub fn succ() -> Term<'static> {
+  Term::abs(
+    "n",
+    Term::abs(
+      "f",
+      Term::abs(
+        "x",
+        Term::app(
+          Term::var("f"),
+          Term::apps([Term::var("n"), Term::var("f"), Term::var("x")]).unwrap(),
+        ),
+      ),
+    ),
+  )
+}
+
+/// Addition, `λm. λn. λf. λx. m f (n f x)`
+pub fn plus() -> Term<'static> {
+  Term::abs(
+    "m",
+    Term::abs(
+      "n",
+      Term::abs(
+        "f",
+        Term::abs(
+          "x",
+          Term::apps([
+            Term::var("m"),
+            Term::var("f"),
+            Term::apps([Term::var("n"), Term::var("f"), Term::var("x")]).unwrap(),
+          ])
+          .unwrap(),
+        ),
+      ),
+    ),
+  )
+}
+
+/// Multiplication, `λm. λn. λf. m (n f)`
+pub fn mult() -> Term<'static> {
+  Term::abs(
+    "m",
+    Term::abs(
+      "n",
+      Term::abs(
+        "f",
+        Term::app(Term::var("m"), Term::app(Term::var("n"), Term::var("f"))),
+      ),
+    ),
+  )
+}
+
+/// Church true, `λx. λy. x`
+pub fn church_true() -> Term<'static> {
+  Term::abs("x", Term::abs("y", Term::var("x")))
+}
+
+/// Church false, `λx. λy. y`
+pub fn church_false() -> Term<'static> {
+  Term::abs("x", Term::abs("y", Term::var("y")))
+}
+
+/// Conjunction, `λp. λq. p q p`
+pub fn and() -> Term<'static> {
+  Term::abs(
+    "p",
+    Term::abs(
+      "q",
+      Term::apps([Term::var("p"), Term::var("q"), Term::var("p")]).unwrap(),
+    ),
+  )
+}
+
+/// Disjunction, `λp. λq. p p q`
+pub fn or() -> Term<'static> {
+  Term::abs(
+    "p",
+    Term::abs(
+      "q",
+      Term::apps([Term::var("p"), Term::var("p"), Term::var("q")]).unwrap(),
+    ),
+  )
+}
+
+/// Negation, `λp. p (λx. λy. y) (λx. λy. x)`
+pub fn not() -> Term<'static> {
+  Term::abs(
+    "p",
+    Term::apps([Term::var("p"), church_false(), church_true()]).unwrap(),
+  )
+}
+
+/// Conditional, `λp. λa. λb. p a b`
+pub fn if_then_else() -> Term<'static> {
+  Term::abs(
+    "p",
+    Term::abs(
+      "a",
+      Term::abs(
+        "b",
+        Term::apps([Term::var("p"), Term::var("a"), Term::var("b")]).unwrap(),
+      ),
+    ),
+  )
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  mod prelude {
+    use super::*;
+
+    use crate::interpreter::Interpreter;
+    use crate::term::Evaluate;
+
+    fn normalize(term: &Term<'static>) -> Term<'static> {
+      Interpreter::new().with_full_normalization().evaluate(term)
+    }
+
+    #[test]
+    fn numeral_decodes() {
+      for n in [0, 1, 2, 7] {
+        assert_eq!(numeral(n).as_church_numeral(), Some(n));
+      }
+    }
+
+    #[test]
+    fn plus_two_three() {
+      let term = Term::apps([plus(), numeral(2), numeral(3)]).unwrap();
+      assert_eq!(normalize(&term).as_church_numeral(), Some(5));
+    }
+
+    #[test]
+    fn mult_and_succ() {
+      let term = Term::apps([mult(), numeral(3), numeral(4)]).unwrap();
+      assert_eq!(normalize(&term).as_church_numeral(), Some(12));
+      let term = Term::app(succ(), numeral(0));
+      assert_eq!(normalize(&term).as_church_numeral(), Some(1));
+    }
+
+    #[test]
+    fn ski_identity() {
+      // S K K behaves like I
+      let term = Term::apps([s(), k(), k(), Term::var("a")]).unwrap();
+      assert_eq!(normalize(&term), Term::var("a"));
+      assert_eq!(normalize(&Term::app(i(), Term::var("a"))), Term::var("a"));
+    }
+
+    #[test]
+    fn boolean_connectives() {
+      let eval_bool = |term: Term<'static>| normalize(&term).as_church_bool();
+      assert_eq!(
+        eval_bool(Term::apps([and(), church_true(), church_false()]).unwrap()),
+        Some(false)
+      );
+      assert_eq!(
+        eval_bool(Term::apps([or(), church_false(), church_true()]).unwrap()),
+        Some(true)
+      );
+      assert_eq!(eval_bool(Term::app(not(), church_false())), Some(true));
+    }
+
+    #[test]
+    fn if_then_else_selects() {
+      let term = Term::apps([
+        if_then_else(),
+        church_false(),
+        Term::var("a"),
+        Term::var("b"),
+      ])
+      .unwrap();
+      assert_eq!(normalize(&term), Term::var("b"));
+    }
+  }
+}
@@ -0,0 +1,21 @@
+//!
+//! Tiny xorshift generator for tests, so random inputs reproduce from a seed
+//! without pulling in a crate
+//!
+
+/// Seeded with any nonzero value
+pub(crate) struct Rng(pub(crate) u64);
+
+impl Rng {
+  pub(crate) fn next(&mut self) -> u64 {
+    self.0 ^= self.0 << 13;
+    self.0 ^= self.0 >> 7;
+    self.0 ^= self.0 << 17;
+    self.0
+  }
+
+  /// Something in `0..n`
+  pub(crate) fn below(&mut self, n: usize) -> usize {
+    (self.next() % n as u64) as usize
+  }
+}
@@ -0,0 +1,148 @@
+//!
+//! Compile terms to SKI combinators by bracket abstraction, removing every binder
+//!
+
+use std::fmt;
+
+use crate::prelude;
+use crate::term::Term;
+
+/// A combinatory logic expression, built from `S`, `K`, `I` and free variables
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SkiTerm<'a> {
+  /// `S x y z = x z (y z)`
+  S,
+  /// `K x y = x`
+  K,
+  /// `I x = x`
+  I,
+  App(Box<SkiTerm<'a>>, Box<SkiTerm<'a>>),
+  Var(&'a str),
+}
+
+impl<'a> SkiTerm<'a> {
+  pub fn app(lhs: SkiTerm<'a>, rhs: SkiTerm<'a>) -> Self {
+    SkiTerm::App(Box::new(lhs), Box::new(rhs))
+  }
+
+  /// Whether `name` occurs anywhere in the expression
+  pub fn mentions(&self, name: &str) -> bool {
+    match self {
+      SkiTerm::Var(var) => *var == name,
+      SkiTerm::App(lhs, rhs) => lhs.mentions(name) || rhs.mentions(name),
+      _ => false,
+    }
+  }
+
+  /// Turn back into a lambda term, spelling each combinator out as its prelude
+  /// definition, so it can be evaluated
+  pub fn to_term(&self) -> Term<'a> {
+    match self {
+      SkiTerm::S => prelude::s(),
+      SkiTerm::K => prelude::k(),
+      SkiTerm::I => prelude::i(),
+      SkiTerm::App(lhs, rhs) => Term::app(lhs.to_term(), rhs.to_term()),
+      SkiTerm::Var(name) => Term::var(*name),
+    }
+  }
+
+  /// Eliminate `name` from the expression, giving `[name] self`, which applied
+  /// to some `e` behaves like `self` with `e` in place of `name`
+  /// - simplified as it goes, so `[x] f x` is just `f` when `f` doesn't mention `x`
+  fn abstract_over(self, name: &str) -> Self {
+    match self {
+      SkiTerm::Var(var) if var == name => SkiTerm::I,
+      body if !body.mentions(name) => SkiTerm::app(SkiTerm::K, body),
+      SkiTerm::App(lhs, rhs) => {
+        if matches!(*rhs, SkiTerm::Var(var) if var == name) && !lhs.mentions(name) {
+          return *lhs;
+        }
+        SkiTerm::app(
+          SkiTerm::app(SkiTerm::S, lhs.abstract_over(name)),
+          rhs.abstract_over(name),
+        )
+      }
+      _ => unreachable!("combinators never mention a name"),
+    }
+  }
+}
+
+/// Printed like a term, with applications associating to the left, e.g. `S (K x) I`
+impl fmt::Display for SkiTerm<'_> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      SkiTerm::S => write!(f, "S"),
+      SkiTerm::K => write!(f, "K"),
+      SkiTerm::I => write!(f, "I"),
+      SkiTerm::Var(name) => write!(f, "{}", name),
+      SkiTerm::App(lhs, rhs) => match **rhs {
+        SkiTerm::App(..) => write!(f, "{} ({})", lhs, rhs),
+        _ => write!(f, "{} {}", lhs, rhs),
+      },
+    }
+  }
+}
+
+impl<'src> Term<'src> {
+  /// Compile to combinators, eliminating every abstraction by bracket abstraction
+  /// - free variables are kept as they are
+  pub fn to_ski(&self) -> SkiTerm<'_> {
+    match self {
+      Term::Variable(name) => SkiTerm::Var(name),
+      Term::Abstraction { param, body } => body.to_ski().abstract_over(param),
+      Term::Application { lhs, rhs } => SkiTerm::app(lhs.to_ski(), rhs.to_ski()),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  mod ski {
+    use super::*;
+
+    use crate::api::parse_str;
+    use crate::interpreter::Interpreter;
+
+    fn compile(input: &str) -> String {
+      parse_str(input).unwrap().to_ski().to_string()
+    }
+
+    #[test]
+    fn to_ski_identity() {
+      assert_eq!(compile("λx. x"), "I");
+    }
+
+    #[test]
+    fn to_ski_constant() {
+      assert_eq!(compile("λx. λy. x"), "K");
+      assert_eq!(compile("λx. λy. y"), "K I");
+    }
+
+    #[test]
+    fn to_ski_keeps_free_variables() {
+      assert_eq!(compile("f x"), "f x");
+      assert_eq!(compile("λx. f"), "K f");
+      assert_eq!(compile("λx. f (g x)"), "S (K f) g");
+    }
+
+    #[test]
+    fn to_ski_behaves_the_same() {
+      for src in ["λf. λx. f (f x)", "λx. λy. y x", "λp. λq. p q p", "λx. x x"] {
+        let term = parse_str(src).unwrap();
+        let ski = term.to_ski();
+        let applied = |term: Term<'_>| {
+          let term = Term::from_spine(term, [Term::var("a"), Term::var("b")]);
+          Interpreter::new()
+            .with_full_normalization()
+            .try_evaluate(&term)
+            .unwrap()
+            .to_owned_term()
+        };
+        let expected = applied(term.clone());
+        assert!(applied(ski.to_term()).alpha_eq(&expected), "{src} as {ski}");
+      }
+    }
+  }
+}
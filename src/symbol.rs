@@ -0,0 +1,203 @@
+//!
+//! Interned names, so comparing two of them is an integer comparison rather than
+//! a string one
+//!
+
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+use crate::term::Term;
+
+/// Handle to a name stored in a `SymbolTable`
+/// - only meaningful alongside the table that produced it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Symbol(u32);
+
+/// Maps each distinct name to a `Symbol` and back
+#[derive(Debug, Default, Clone)]
+pub struct SymbolTable {
+  names: Vec<String>,
+  symbols: HashMap<String, Symbol>,
+}
+
+impl SymbolTable {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Get the symbol for a name, adding it to the table if it's new
+  pub fn intern(&mut self, name: &str) -> Symbol {
+    if let Some(&symbol) = self.symbols.get(name) {
+      return symbol;
+    }
+    let symbol = Symbol(self.names.len() as u32);
+    self.names.push(name.to_string());
+    self.symbols.insert(name.to_string(), symbol);
+    symbol
+  }
+
+  /// The name a symbol stands for
+  pub fn resolve(&self, symbol: Symbol) -> &str {
+    &self.names[symbol.0 as usize]
+  }
+
+  /// Number of distinct names interned so far
+  pub fn len(&self) -> usize {
+    self.names.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.names.is_empty()
+  }
+
+  /// Mint a symbol for the first of `base'`, `base''`, ... that isn't in `used`
+  pub(crate) fn fresh(&mut self, base: Symbol, used: &HashSet<Symbol>) -> Symbol {
+    let mut name = self.resolve(base).to_string();
+    loop {
+      name.push('\'');
+      let symbol = self.intern(&name);
+      if !used.contains(&symbol) {
+        return symbol;
+      }
+    }
+  }
+}
+
+/// A `Term` with every name replaced by its `Symbol`, see `Term::intern`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum InternedTerm {
+  Variable(Symbol),
+  Abstraction {
+    param: Symbol,
+    body: Rc<InternedTerm>,
+  },
+  Application {
+    lhs: Rc<InternedTerm>,
+    rhs: Rc<InternedTerm>,
+  },
+}
+
+impl InternedTerm {
+  /// Collect every variable that occurs without an enclosing binder
+  pub fn free_variables(&self) -> HashSet<Symbol> {
+    match self {
+      InternedTerm::Variable(name) => HashSet::from([*name]),
+      InternedTerm::Abstraction { param, body } => {
+        let mut free = body.free_variables();
+        free.remove(param);
+        free
+      }
+      InternedTerm::Application { lhs, rhs } => {
+        let mut free = lhs.free_variables();
+        free.extend(rhs.free_variables());
+        free
+      }
+    }
+  }
+
+  /// Substitute free occurrences of a variable with a given term, α-converting
+  /// binders that would otherwise capture a free variable of the value
+  /// - fresh binder names are primed, like `fresh::FreshNamer`, and added to `table`
+  pub fn substitute(&self, var: Symbol, value: &InternedTerm, table: &mut SymbolTable) -> Self {
+    match self {
+      InternedTerm::Variable(name) if *name == var => value.clone(),
+      InternedTerm::Abstraction { param, body } if *param != var => {
+        let value_free = value.free_variables();
+        let body_free = body.free_variables();
+        if value_free.contains(param) && body_free.contains(&var) {
+          // rename the binder to something neither side can see
+          let mut used = body_free;
+          used.extend(value_free);
+          used.insert(var);
+          let fresh = table.fresh(*param, &used);
+          let renamed = body.substitute(*param, &InternedTerm::Variable(fresh), table);
+          InternedTerm::Abstraction {
+            param: fresh,
+            body: Rc::new(renamed.substitute(var, value, table)),
+          }
+        } else {
+          InternedTerm::Abstraction {
+            param: *param,
+            body: Rc::new(body.substitute(var, value, table)),
+          }
+        }
+      }
+      InternedTerm::Application { lhs, rhs } => InternedTerm::Application {
+        lhs: Rc::new(lhs.substitute(var, value, table)),
+        rhs: Rc::new(rhs.substitute(var, value, table)),
+      },
+      // nothing to substitute
+      _ => self.clone(),
+    }
+  }
+
+  /// Turn the symbols back into names
+  pub fn resolve(&self, table: &SymbolTable) -> Term<'static> {
+    match self {
+      InternedTerm::Variable(name) => Term::var(table.resolve(*name).to_string()),
+      InternedTerm::Abstraction { param, body } => {
+        Term::abs(table.resolve(*param).to_string(), body.resolve(table))
+      }
+      InternedTerm::Application { lhs, rhs } => Term::app(lhs.resolve(table), rhs.resolve(table)),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  mod symbol_table {
+    use super::*;
+
+    use crate::api::parse_str;
+    use crate::fresh::FreshNamer;
+
+    #[test]
+    fn equal_names_equal_symbols() {
+      let mut table = SymbolTable::new();
+      let x = table.intern("x");
+      let y = table.intern("y");
+      assert_ne!(x, y);
+      assert_eq!(table.intern("x"), x);
+      assert_eq!(table.len(), 2);
+      assert_eq!(table.resolve(y), "y");
+    }
+
+    #[test]
+    fn intern_preserves_structure() {
+      let mut table = SymbolTable::new();
+      for input in ["x", "λx. x y", "(λf. λx. f (f x)) (λy. y) z"] {
+        let term = parse_str(input).unwrap();
+        let interned = term.intern(&mut table);
+        assert_eq!(interned.resolve(&table), term);
+      }
+      // every occurrence of a name shares one symbol
+      let term = parse_str("λx. x x").unwrap().intern(&mut table);
+      let x = table.intern("x");
+      let expected = InternedTerm::Abstraction {
+        param: x,
+        body: Rc::new(InternedTerm::Application {
+          lhs: Rc::new(InternedTerm::Variable(x)),
+          rhs: Rc::new(InternedTerm::Variable(x)),
+        }),
+      };
+      assert_eq!(term, expected);
+    }
+
+    #[test]
+    fn substitute_matches_term() {
+      // (λy. x y)[x := y] has to rename the binder
+      let term = parse_str("λy. x y").unwrap();
+      let value = parse_str("y").unwrap();
+      let expected = term.substitute_with("x", &value, &mut FreshNamer::new(), &mut 0);
+
+      let mut table = SymbolTable::new();
+      let interned = term.intern(&mut table);
+      let x = table.intern("x");
+      let value = value.intern(&mut table);
+      let substituted = interned.substitute(x, &value, &mut table);
+      assert_eq!(substituted.resolve(&table), expected);
+    }
+  }
+}
@@ -2,45 +2,878 @@
 //! A term is some rule in our grammar
 //!
 
+use std::borrow::Cow;
+use std::cell::OnceCell;
+use std::collections::HashSet;
 use std::fmt;
+use std::rc::Rc;
+
+use crate::fresh::{FreshNamer, NameGen};
+use crate::interpreter::EvalError;
+use crate::symbol::{InternedTerm, SymbolTable};
+use crate::token::Token;
 
 /// Abstract syntax tree built from the BNF grammar
-#[derive(Debug, Clone, PartialEq)]
+///
+/// Names usually borrow from the source, but may be owned when they are minted
+/// during evaluation (see `fresh::NameGen`). Subterms are reference counted, so
+/// cloning is shallow and substitution shares whatever it leaves untouched
+///
+/// Terms are ordered by variant first (`Variable < Abstraction < Application`),
+/// then by their contents from left to right. Binder names take part, so the order
+/// is not α-insensitive, `canonicalize` first if that matters
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Term<'src> {
-  Variable(&'src str),
+  Variable(Cow<'src, str>),
   Abstraction {
-    param: &'src str,
-    body: Box<Term<'src>>,
+    param: Cow<'src, str>,
+    body: Rc<Term<'src>>,
   },
   Application {
-    lhs: Box<Term<'src>>,
-    rhs: Box<Term<'src>>,
+    lhs: Rc<Term<'src>>,
+    rhs: Rc<Term<'src>>,
   },
 }
 
+/// A term that owns all of its names, so it can outlive the source it was parsed
+/// from, see `Term::to_owned_term`
+pub type OwnedTerm = Term<'static>;
+
+impl<'src> Term<'src> {
+  pub fn var(name: impl Into<Cow<'src, str>>) -> Self {
+    Term::Variable(name.into())
+  }
+
+  pub fn abs(param: impl Into<Cow<'src, str>>, body: Term<'src>) -> Self {
+    Term::Abstraction {
+      param: param.into(),
+      body: Rc::new(body),
+    }
+  }
+
+  pub fn app(lhs: Term<'src>, rhs: Term<'src>) -> Self {
+    Term::Application {
+      lhs: Rc::new(lhs),
+      rhs: Rc::new(rhs),
+    }
+  }
+
+  /// Left-fold a sequence of terms into applications, so `[f, a, b]` becomes `(f a) b`
+  /// - returns `None` when given no terms at all
+  pub fn apps(terms: impl IntoIterator<Item = Term<'src>>) -> Option<Self> {
+    terms.into_iter().reduce(Term::app)
+  }
+
+  /// Rebuild an application spine, so `f` with `[a, b]` becomes `(f a) b`
+  /// - the inverse of `spine`
+  pub fn from_spine(head: Term<'src>, args: impl IntoIterator<Item = Term<'src>>) -> Self {
+    args.into_iter().fold(head, Term::app)
+  }
+
+  /// Wrap a body in nested abstractions, so `[x, y]` with `b` becomes `λx. λy. b`,
+  /// the abstraction counterpart of `from_spine`
+  pub fn lambda_chain(
+    params: impl IntoIterator<Item = impl Into<Cow<'src, str>>>,
+    body: Term<'src>,
+  ) -> Self {
+    let params: Vec<_> = params.into_iter().map(Into::into).collect();
+    params
+      .into_iter()
+      .rev()
+      .fold(body, |body, param| Term::abs(param, body))
+  }
+
+  /// Flatten nested left-associated applications, so `f a b` gives head `f` and
+  /// arguments `[a, b]`
+  /// - anything that isn't an application is its own head with no arguments
+  pub fn spine(&self) -> (&Self, Vec<&Self>) {
+    let mut head = self;
+    let mut args = Vec::new();
+    while let Term::Application { lhs, rhs } = head {
+      args.push(rhs.as_ref());
+      head = lhs;
+    }
+    args.reverse();
+    (head, args)
+  }
+
+  /// Visit the arguments of `spine` in order without collecting them
+  pub fn for_each_spine_arg(&self, mut f: impl FnMut(&Self)) {
+    fn go<'a, 'src>(term: &'a Term<'src>, f: &mut impl FnMut(&'a Term<'src>)) {
+      if let Term::Application { lhs, rhs } = term {
+        go(lhs, f);
+        f(rhs);
+      }
+    }
+    go(self, &mut f)
+  }
+
+  /// The immediate subterms, left to right: none for a variable, the body of an
+  /// abstraction, and both sides of an application
+  pub fn children(&self) -> Vec<&Self> {
+    match self {
+      Term::Variable(_) => Vec::new(),
+      Term::Abstraction { body, .. } => vec![body],
+      Term::Application { lhs, rhs } => vec![lhs, rhs],
+    }
+  }
+
+  /// Total number of nodes in the tree
+  pub fn size(&self) -> usize {
+    match self {
+      Term::Variable(_) => 1,
+      Term::Abstraction { body, .. } => 1 + body.size(),
+      Term::Application { lhs, rhs } => 1 + lhs.size() + rhs.size(),
+    }
+  }
+
+  /// Number of nodes on the longest path from the root to a variable
+  pub fn depth(&self) -> usize {
+    match self {
+      Term::Variable(_) => 1,
+      Term::Abstraction { body, .. } => 1 + body.depth(),
+      Term::Application { lhs, rhs } => 1 + lhs.depth().max(rhs.depth()),
+    }
+  }
+
+  /// Copy the term so that it no longer borrows from the source
+  pub fn to_owned_term(&self) -> OwnedTerm {
+    match self {
+      Term::Variable(name) => Term::Variable(Cow::Owned(name.to_string())),
+      Term::Abstraction { param, body } => Term::Abstraction {
+        param: Cow::Owned(param.to_string()),
+        body: Rc::new(body.to_owned_term()),
+      },
+      Term::Application { lhs, rhs } => Term::Application {
+        lhs: Rc::new(lhs.to_owned_term()),
+        rhs: Rc::new(rhs.to_owned_term()),
+      },
+    }
+  }
+
+  /// Replace every name with a symbol from `table`, so comparing names becomes
+  /// comparing integers
+  pub fn intern(&self, table: &mut SymbolTable) -> InternedTerm {
+    match self {
+      Term::Variable(name) => InternedTerm::Variable(table.intern(name)),
+      Term::Abstraction { param, body } => InternedTerm::Abstraction {
+        param: table.intern(param),
+        body: Rc::new(body.intern(table)),
+      },
+      Term::Application { lhs, rhs } => InternedTerm::Application {
+        lhs: Rc::new(lhs.intern(table)),
+        rhs: Rc::new(rhs.intern(table)),
+      },
+    }
+  }
+
+  /// Rename every variable, binders included, keeping a name wherever `f` gives
+  /// `None`
+  /// - a purely syntactic renaming, so mapping two names onto one can change
+  ///   what a variable refers to
+  pub fn rename(&self, f: impl Fn(&str) -> Option<&'src str>) -> Term<'src> {
+    fn go<'src>(term: &Term<'src>, f: &impl Fn(&str) -> Option<&'src str>) -> Term<'src> {
+      let rename = |name: &Cow<'src, str>| f(name).map_or_else(|| name.clone(), Cow::Borrowed);
+      match term {
+        Term::Variable(name) => Term::Variable(rename(name)),
+        Term::Abstraction { param, body } => Term::Abstraction {
+          param: rename(param),
+          body: Rc::new(go(body, f)),
+        },
+        Term::Application { lhs, rhs } => Term::app(go(lhs, f), go(rhs, f)),
+      }
+    }
+    go(self, &f)
+  }
+
+  /// Like `rename`, but only free occurrences are renamed, binders and the
+  /// variables they bind are left alone
+  /// - nothing is α-converted, so a new name can be captured by a binder
+  pub fn rename_free(&self, f: impl Fn(&str) -> Option<&'src str>) -> Term<'src> {
+    fn go<'a, 'src>(
+      term: &'a Term<'src>,
+      bound: &mut Vec<&'a str>,
+      f: &impl Fn(&str) -> Option<&'src str>,
+    ) -> Term<'src> {
+      match term {
+        Term::Variable(name) => match f(name) {
+          Some(renamed) if !bound.contains(&name.as_ref()) => Term::var(renamed),
+          _ => term.clone(),
+        },
+        Term::Abstraction { param, body } => {
+          bound.push(param);
+          let body = go(body, bound, f);
+          bound.pop();
+          Term::Abstraction {
+            param: param.clone(),
+            body: Rc::new(body),
+          }
+        }
+        Term::Application { lhs, rhs } => Term::app(go(lhs, bound, f), go(rhs, bound, f)),
+      }
+    }
+    go(self, &mut Vec::new(), &f)
+  }
+
+  /// Undo the priming done by α-conversion where it's no longer needed, so a
+  /// binder `y'` goes back to `y` (or `y''` to `y'`, failing that) as long as
+  /// that captures nothing
+  /// - only trailing primes are stripped, as minted by `FreshNamer`
+  /// - the result is always α-equivalent to the original
+  pub fn restore_names(&self) -> Term<'src> {
+    /// Whether a free `old` in `term` sits under a binder for `new`, so that
+    /// renaming it would be captured
+    fn captures(term: &Term<'_>, old: &str, new: &str) -> bool {
+      match term {
+        Term::Variable(_) => false,
+        Term::Abstraction { param, .. } if param == old => false,
+        Term::Abstraction { param, body } => {
+          if param == new && body.free_variables().contains(old) {
+            return true;
+          }
+          captures(body, old, new)
+        }
+        Term::Application { lhs, rhs } => captures(lhs, old, new) || captures(rhs, old, new),
+      }
+    }
+    match self {
+      Term::Variable(_) => self.clone(),
+      Term::Abstraction { param, body } => {
+        let free = body.free_variables();
+        let base = param.trim_end_matches('\'');
+        // the least primed candidate first
+        let renamed = (base.len()..param.len())
+          .map(|end| &param[..end])
+          .find(|candidate| {
+            !candidate.is_empty() && !free.contains(candidate) && !captures(body, param, candidate)
+          })
+          .map(str::to_string);
+        match renamed {
+          Some(renamed) => {
+            let renamed: Cow<'src, str> = Cow::Owned(renamed);
+            // nothing can be captured, so this never α-converts
+            let body = body.substitute(param, &Term::Variable(renamed.clone()));
+            Term::Abstraction {
+              param: renamed,
+              body: Rc::new(body.restore_names()),
+            }
+          }
+          None => Term::Abstraction {
+            param: param.clone(),
+            body: Rc::new(body.restore_names()),
+          },
+        }
+      }
+      Term::Application { lhs, rhs } => Term::app(lhs.restore_names(), rhs.restore_names()),
+    }
+  }
+
+  /// Rename every binder to `v0`, `v1`, ... in the order they appear, leaving
+  /// free variables untouched, so that α-equivalent terms become equal
+  /// - handy as a key for hashing or deduplicating terms up to α-equivalence
+  pub fn canonicalize(&self) -> Term<'static> {
+    fn go<'a>(
+      term: &'a Term<'_>,
+      free: &HashSet<&str>,
+      scope: &mut Vec<(&'a str, String)>,
+      next: &mut usize,
+    ) -> Term<'static> {
+      match term {
+        Term::Variable(name) => {
+          let renamed = scope
+            .iter()
+            .rev()
+            .find(|(bound, _)| bound == name)
+            .map_or_else(|| name.to_string(), |(_, renamed)| renamed.clone());
+          Term::Variable(Cow::Owned(renamed))
+        }
+        Term::Abstraction { param, body } => {
+          // skip over any generated name that would clash with a free variable
+          let renamed = loop {
+            let candidate = format!("v{next}");
+            *next += 1;
+            if !free.contains(candidate.as_str()) {
+              break candidate;
+            }
+          };
+          scope.push((param, renamed.clone()));
+          let body = go(body, free, scope, next);
+          scope.pop();
+          Term::Abstraction {
+            param: Cow::Owned(renamed),
+            body: Rc::new(body),
+          }
+        }
+        Term::Application { lhs, rhs } => Term::Application {
+          lhs: Rc::new(go(lhs, free, scope, next)),
+          rhs: Rc::new(go(rhs, free, scope, next)),
+        },
+      }
+    }
+    go(self, &self.free_variables(), &mut Vec::new(), &mut 0)
+  }
+
+  /// Collect every variable that occurs without an enclosing binder
+  pub fn free_variables(&self) -> HashSet<&str> {
+    match self {
+      Term::Variable(name) => HashSet::from([name.as_ref()]),
+      Term::Abstraction { param, body } => {
+        let mut free = body.free_variables();
+        free.remove(param.as_ref());
+        free
+      }
+      Term::Application { lhs, rhs } => {
+        let mut free = lhs.free_variables();
+        free.extend(rhs.free_variables());
+        free
+      }
+    }
+  }
+
+  /// Whether no variable occurs free
+  pub fn is_closed(&self) -> bool {
+    let mut closed = true;
+    self.for_each_free_var(|_| closed = false);
+    closed
+  }
+
+  /// Whether the term is a closed abstraction, i.e. a proper combinator
+  /// - a closed application like `(λx. x) (λx. x)` isn't one, though it reduces
+  ///   to one
+  pub fn is_combinator(&self) -> bool {
+    matches!(self, Term::Abstraction { .. }) && self.is_closed()
+  }
+
+  /// Visit every free occurrence of a variable without building a set
+  /// - names come as `&str` rather than `&'src str`, since they may be owned
+  /// - a name is visited once per occurrence, so callers wanting `free_variables`
+  ///   semantics should deduplicate
+  pub fn for_each_free_var(&self, mut f: impl FnMut(&str)) {
+    fn go<'a>(term: &'a Term<'_>, bound: &mut Vec<&'a str>, f: &mut impl FnMut(&str)) {
+      match term {
+        Term::Variable(name) => {
+          if !bound.contains(&name.as_ref()) {
+            f(name)
+          }
+        }
+        Term::Abstraction { param, body } => {
+          bound.push(param);
+          go(body, bound, f);
+          bound.pop();
+        }
+        Term::Application { lhs, rhs } => {
+          go(lhs, bound, f);
+          go(rhs, bound, f);
+        }
+      }
+    }
+    go(self, &mut Vec::new(), &mut f)
+  }
+
+  /// Substitute free occurrences of a variable with a given term, `self[var := value]`,
+  /// α-converting binders that would otherwise capture a free variable of the value
+  /// - renamed binders are primed, as with `FreshNamer`
+  /// - subtrees without a free `var` are shared with `self`, not copied
+  pub fn substitute(&self, var: &str, value: &Term<'src>) -> Term<'src> {
+    self.substitute_with(var, value, &mut FreshNamer::new(), &mut 0)
+  }
+
+  /// Substitute free occurrences of a variable with a given term, α-converting
+  /// binders that would otherwise capture a free variable of the value
+  /// - fresh binder names come from `namer`, and each replaced occurrence is
+  ///   counted in `replaced`
+  pub(crate) fn substitute_with(
+    &self,
+    var: &str,
+    value: &Term<'src>,
+    namer: &mut dyn NameGen,
+    replaced: &mut usize,
+  ) -> Term<'src> {
+    // the value's free variables are the same at every binder, so collect them at
+    // most once, and only if a binder is actually reached
+    self
+      .substitute_in(var, value, &OnceCell::new(), namer, replaced)
+      .unwrap_or_else(|| self.clone())
+  }
+
+  /// Does the work of `substitute_with`, giving `None` when nothing changed so
+  /// the caller can keep sharing the original subtree
+  fn substitute_in<'v>(
+    &self,
+    var: &str,
+    value: &'v Term<'src>,
+    value_free: &OnceCell<HashSet<&'v str>>,
+    namer: &mut dyn NameGen,
+    replaced: &mut usize,
+  ) -> Option<Term<'src>> {
+    match self {
+      Term::Variable(name) if name == var => {
+        *replaced += 1;
+        Some(value.clone())
+      }
+      Term::Abstraction { param, body } if param != var => {
+        let free = value_free.get_or_init(|| value.free_variables());
+        if free.contains(param.as_ref()) && body.free_variables().contains(var) {
+          // rename the binder to something neither side can see
+          let mut used = body.free_variables();
+          used.extend(free);
+          used.insert(var);
+          let fresh: Cow<'src, str> = namer.fresh(param, &used).into();
+          let renamed =
+            Rc::new(body.substitute_with(param, &Term::Variable(fresh.clone()), namer, replaced));
+          Some(Term::Abstraction {
+            param: fresh,
+            body: renamed
+              .substitute_in(var, value, value_free, namer, replaced)
+              .map_or(renamed, Rc::new),
+          })
+        } else {
+          Some(Term::Abstraction {
+            param: param.clone(),
+            body: Rc::new(body.substitute_in(var, value, value_free, namer, replaced)?),
+          })
+        }
+      }
+      Term::Application { lhs, rhs } => {
+        // rebuild only the side something actually changed beneath
+        let new_lhs = lhs.substitute_in(var, value, value_free, namer, replaced);
+        let new_rhs = rhs.substitute_in(var, value, value_free, namer, replaced);
+        if new_lhs.is_none() && new_rhs.is_none() {
+          return None;
+        }
+        Some(Term::Application {
+          lhs: new_lhs.map_or_else(|| Rc::clone(lhs), Rc::new),
+          rhs: new_rhs.map_or_else(|| Rc::clone(rhs), Rc::new),
+        })
+      }
+      // nothing to substitute
+      _ => None,
+    }
+  }
+
+  /// Decode a Church boolean, `λx. λy. x` being true and `λx. λy. y` false
+  pub fn as_church_bool(&self) -> Option<bool> {
+    let Term::Abstraction { param: t, body } = self else {
+      return None;
+    };
+    let Term::Abstraction { param: f, body } = &**body else {
+      return None;
+    };
+    match &**body {
+      // check the inner binder first, it shadows the outer one in `λx. λx. x`
+      Term::Variable(name) if name == f => Some(false),
+      Term::Variable(name) if name == t => Some(true),
+      _ => None,
+    }
+  }
+
+  /// Decode a Church numeral, counting the applications of `f` in `λf. λx. f (f ... x)`
+  pub fn as_church_numeral(&self) -> Option<u64> {
+    let Term::Abstraction { param: f, body } = self else {
+      return None;
+    };
+    let Term::Abstraction { param: x, body } = &**body else {
+      return None;
+    };
+    // `λf. λf. f` would have its successor shadowed
+    if f == x {
+      return matches!(&**body, Term::Variable(name) if name == x).then_some(0);
+    }
+    let mut count = 0;
+    let mut current = &**body;
+    loop {
+      match current {
+        Term::Variable(name) if name == x => return Some(count),
+        Term::Application { lhs, rhs } if matches!(&**lhs, Term::Variable(name) if name == f) => {
+          count += 1;
+          current = rhs;
+        }
+        _ => return None,
+      }
+    }
+  }
+
+  /// Collect every name introduced by an abstraction anywhere in the tree
+  pub fn bound_variables(&self) -> HashSet<&str> {
+    match self {
+      Term::Variable(_) => HashSet::new(),
+      Term::Abstraction { param, body } => {
+        let mut bound = body.bound_variables();
+        bound.insert(param);
+        bound
+      }
+      Term::Application { lhs, rhs } => {
+        let mut bound = lhs.bound_variables();
+        bound.extend(rhs.bound_variables());
+        bound
+      }
+    }
+  }
+
+  /// Whether some abstraction rebinds a name already bound by an enclosing one,
+  /// as in `λx. λx. x`
+  pub fn has_shadowing(&self) -> bool {
+    fn go<'a>(term: &'a Term<'_>, enclosing: &mut Vec<&'a str>) -> bool {
+      match term {
+        Term::Variable(_) => false,
+        Term::Abstraction { param, body } => {
+          if enclosing.contains(&param.as_ref()) {
+            return true;
+          }
+          enclosing.push(param);
+          let shadowed = go(body, enclosing);
+          enclosing.pop();
+          shadowed
+        }
+        Term::Application { lhs, rhs } => go(lhs, enclosing) || go(rhs, enclosing),
+      }
+    }
+    go(self, &mut Vec::new())
+  }
+
+  /// Whether this node is an abstraction applied to something, i.e. a β-redex
+  pub fn is_redex(&self) -> bool {
+    matches!(self, Term::Application { lhs, .. } if matches!(**lhs, Term::Abstraction { .. }))
+  }
+
+  /// Whether there are no β-redexes anywhere, including under binders
+  pub fn is_normal_form(&self) -> bool {
+    match self {
+      Term::Variable(_) => true,
+      Term::Abstraction { body, .. } => body.is_normal_form(),
+      Term::Application { lhs, rhs } => {
+        !self.is_redex() && lhs.is_normal_form() && rhs.is_normal_form()
+      }
+    }
+  }
+
+  /// Whether the term looks like `λx1. ... λxn. y M1 ... Mk`, with a variable at the
+  /// head, so no reduction can change its outer shape
+  /// - the arguments `Mi` may still contain redexes
+  pub fn is_head_normal_form(&self) -> bool {
+    let mut term = self;
+    while let Term::Abstraction { body, .. } = term {
+      term = body;
+    }
+    matches!(term.spine().0, Term::Variable(_))
+  }
+
+  /// Structural equality up to consistent renaming of bound variables
+  pub fn alpha_eq(&self, other: &Term<'_>) -> bool {
+    fn go<'a>(lhs: &'a Term<'_>, rhs: &'a Term<'_>, binders: &mut Vec<(&'a str, &'a str)>) -> bool {
+      match (lhs, rhs) {
+        (Term::Variable(x), Term::Variable(y)) => {
+          // the innermost binder of each name decides what it refers to
+          let x_binder = binders.iter().rposition(|(bound, _)| bound == x);
+          let y_binder = binders.iter().rposition(|(_, bound)| bound == y);
+          match (x_binder, y_binder) {
+            (Some(i), Some(j)) => i == j,
+            (None, None) => x == y,
+            _ => false,
+          }
+        }
+        (Term::Abstraction { param: p, body: b1 }, Term::Abstraction { param: q, body: b2 }) => {
+          binders.push((p, q));
+          let equal = go(b1, b2, binders);
+          binders.pop();
+          equal
+        }
+        (Term::Application { lhs: l1, rhs: r1 }, Term::Application { lhs: l2, rhs: r2 }) => {
+          go(l1, l2, binders) && go(r1, r2, binders)
+        }
+        _ => false,
+      }
+    }
+    go(self, other, &mut Vec::new())
+  }
+
+  /// The subterm reached by following `path` down from here, if it leads anywhere
+  pub fn at(&self, path: &[Step]) -> Option<&Self> {
+    path.iter().try_fold(self, |term, step| match (term, step) {
+      (Term::Abstraction { body, .. }, Step::Body) => Some(&**body),
+      (Term::Application { lhs, .. }, Step::Lhs) => Some(&**lhs),
+      (Term::Application { rhs, .. }, Step::Rhs) => Some(&**rhs),
+      _ => None,
+    })
+  }
+
+  /// Find the first node, in pre-order, where two terms differ structurally
+  /// - `None` when they're equal, binder names included, so `λx. x` and `λy. y`
+  ///   differ at the root
+  pub fn diff(&self, other: &Term<'src>) -> Option<TermDiff<'src>> {
+    let mut path = Vec::new();
+    let (mut lhs, mut rhs) = (self, other);
+    let mut pending = Vec::new();
+    loop {
+      match (lhs, rhs) {
+        (Term::Variable(x), Term::Variable(y)) if x == y => {}
+        (
+          Term::Abstraction {
+            param: x,
+            body: x_body,
+          },
+          Term::Abstraction {
+            param: y,
+            body: y_body,
+          },
+        ) if x == y => {
+          path.push(Step::Body);
+          (lhs, rhs) = (x_body, y_body);
+          continue;
+        }
+        (
+          Term::Application {
+            lhs: x_lhs,
+            rhs: x_rhs,
+          },
+          Term::Application {
+            lhs: y_lhs,
+            rhs: y_rhs,
+          },
+        ) => {
+          pending.push((path.len(), &**x_rhs, &**y_rhs));
+          path.push(Step::Lhs);
+          (lhs, rhs) = (x_lhs, y_lhs);
+          continue;
+        }
+        _ => {
+          return Some(TermDiff {
+            path,
+            left: lhs.clone(),
+            right: rhs.clone(),
+          })
+        }
+      }
+      // this branch matched, so backtrack to the innermost right-hand side left
+      let (depth, x_rhs, y_rhs) = pending.pop()?;
+      path.truncate(depth);
+      path.push(Step::Rhs);
+      (lhs, rhs) = (x_rhs, y_rhs);
+    }
+  }
+}
+
+/// One step down from a term into one of its subterms
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step {
+  /// Into the body of an abstraction
+  Body,
+  /// Into the function of an application
+  Lhs,
+  /// Into the argument of an application
+  Rhs,
+}
+
+/// Where two terms first differ, see `Term::diff`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TermDiff<'src> {
+  /// Steps from the root down to the differing nodes
+  pub path: Vec<Step>,
+  /// The subterm found there on the left
+  pub left: Term<'src>,
+  /// The subterm found there on the right
+  pub right: Term<'src>,
+}
+
+impl fmt::Display for TermDiff<'_> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "at ")?;
+    if self.path.is_empty() {
+      write!(f, "root")?;
+    }
+    for (i, step) in self.path.iter().enumerate() {
+      let step = match step {
+        Step::Body => "body",
+        Step::Lhs => "lhs",
+        Step::Rhs => "rhs",
+      };
+      write!(f, "{}{}", if i == 0 { "" } else { "." }, step)?;
+    }
+    write!(f, ": `{}` vs `{}`", self.left, self.right)
+  }
+}
+
 /// Defines a way to transform some root term to its simplified version
 pub trait Evaluate<'src> {
   fn evaluate(&mut self, term: &Term<'src>) -> Term<'src>;
+
+  /// Like `evaluate`, but surfacing any limit being hit as an error
+  /// - by default this never fails, for evaluators without limits
+  fn try_evaluate(&mut self, term: &Term<'src>) -> Result<Term<'src>, EvalError> {
+    Ok(self.evaluate(term))
+  }
 }
 
-impl<'src> fmt::Display for Term<'src> {
-  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+/// Knobs controlling how a term is rendered, see `Term::display_with`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DisplayOptions {
+  /// Glyph introducing an abstraction
+  pub lambda: char,
+  /// Render Church numerals as their decimal value, e.g. `2` for `λf. λx. f (f x)`
+  /// - note `λx. λy. y` is both zero and false, so it would render as `0`
+  pub numerals: bool,
+  /// Merge directly nested abstractions into one binder list, e.g. `λx y. x` for
+  /// `λx. λy. x`, as the parser accepts
+  pub collapse_binders: bool,
+}
+
+impl DisplayOptions {
+  /// Render with only ASCII characters, i.e. `\x. x`
+  pub fn ascii() -> Self {
+    Self {
+      lambda: '\\',
+      numerals: false,
+      collapse_binders: false,
+    }
+  }
+}
+
+impl Default for DisplayOptions {
+  fn default() -> Self {
+    Self {
+      lambda: 'λ',
+      numerals: false,
+      collapse_binders: false,
+    }
+  }
+}
+
+/// A term paired with the options to render it with
+pub struct TermDisplay<'a, 'src> {
+  term: &'a Term<'src>,
+  options: DisplayOptions,
+}
+
+impl<'src> Term<'src> {
+  /// Render the term with something other than the default options
+  pub fn display_with(&self, options: DisplayOptions) -> TermDisplay<'_, 'src> {
+    TermDisplay {
+      term: self,
+      options,
+    }
+  }
+
+  /// Render the term using `\` in place of `λ`
+  pub fn display_ascii(&self) -> TermDisplay<'_, 'src> {
+    self.display_with(DisplayOptions::ascii())
+  }
+
+  /// Render any Church numerals within the term as decimal values
+  pub fn display_numerals(&self) -> TermDisplay<'_, 'src> {
+    self.display_with(DisplayOptions {
+      numerals: true,
+      ..DisplayOptions::default()
+    })
+  }
+
+  /// Render directly nested abstractions under a single `λ`, as in `λx y. x`
+  pub fn display_collapsed(&self) -> TermDisplay<'_, 'src> {
+    self.display_with(DisplayOptions {
+      collapse_binders: true,
+      ..DisplayOptions::default()
+    })
+  }
+
+  /// The numeral to render this term as, if the options ask for that
+  fn display_numeral(&self, options: &DisplayOptions) -> Option<u64> {
+    options.numerals.then(|| self.as_church_numeral()).flatten()
+  }
+
+  /// Write the term with as few parentheses as the grammar allows to read it
+  /// back unchanged
+  /// - an abstraction body is never parenthesized, it extends as far right as possible
+  /// - the left side of an application is parenthesized only when it's an
+  ///   abstraction, which would otherwise swallow the argument
+  /// - the right side is parenthesized unless it's a variable, since only an
+  ///   `atom` may appear there
+  /// - a numeral rendered as a number is an atom, so never needs parentheses
+  /// - names are written as their tokens would be, escaping any keyword
+  /// - nested binders collapse into one list only when asked to, and never into
+  ///   an abstraction rendered as a numeral
+  fn fmt_with(&self, f: &mut fmt::Formatter<'_>, options: &DisplayOptions) -> fmt::Result {
+    if let Some(n) = self.display_numeral(options) {
+      return write!(f, "{}", n);
+    }
     match self {
-      Term::Variable(name) => write!(f, "{}", name),
-      Term::Abstraction { param, body } => write!(f, "λ{}. {}", param, body),
+      Term::Variable(name) => write!(f, "{}", Token::Binding(name)),
+      Term::Abstraction { param, body } => {
+        write!(f, "{}{}", options.lambda, Token::Binding(param))?;
+        let mut body: &Term<'_> = body;
+        while let Term::Abstraction { param, body: inner } = body {
+          if !options.collapse_binders || body.display_numeral(options).is_some() {
+            break;
+          }
+          write!(f, " {}", Token::Binding(param))?;
+          body = inner;
+        }
+        write!(f, ". ")?;
+        body.fmt_with(f, options)
+      }
       Term::Application { lhs, rhs } => {
-        // insert parens for clarity in nested applications (show left-associative nature)
-        let lhs_str = match **lhs {
-          Term::Abstraction { .. } | Term::Application { .. } => format!("({})", lhs),
-          _ => format!("{}", lhs),
-        };
-        let rhs_str = match **rhs {
-          Term::Abstraction { .. } | Term::Application { .. } => format!("({})", rhs),
-          _ => format!("{}", rhs),
-        };
-        write!(f, "{} {}", lhs_str, rhs_str)
+        // application is left-associative, so a nested application on the left needs no
+        // parens; an abstraction would swallow everything after it, so it always does
+        let atomic = |term: &Term<'_>| term.display_numeral(options).is_some();
+        match **lhs {
+          Term::Abstraction { .. } if !atomic(lhs) => lhs.fmt_parenthesized(f, options)?,
+          _ => lhs.fmt_with(f, options)?,
+        }
+        write!(f, " ")?;
+        match **rhs {
+          Term::Variable(_) => rhs.fmt_with(f, options),
+          _ if atomic(rhs) => rhs.fmt_with(f, options),
+          _ => rhs.fmt_parenthesized(f, options),
+        }
+      }
+    }
+  }
+
+  fn fmt_parenthesized(&self, f: &mut fmt::Formatter<'_>, options: &DisplayOptions) -> fmt::Result {
+    write!(f, "(")?;
+    self.fmt_with(f, options)?;
+    write!(f, ")")
+  }
+
+  /// Render the syntax tree as a Graphviz `digraph`, e.g. for `dot -Tsvg`
+  /// - nodes are labelled with the variable name, `λparam` or `@` for applications
+  /// - ids are numbered in pre-order, so a repeated (or shared) subterm still gets
+  ///   its own nodes
+  pub fn to_dot(&self) -> String {
+    fn go(term: &Term<'_>, out: &mut String, next: &mut usize) -> usize {
+      let id = *next;
+      *next += 1;
+      let label = match term {
+        Term::Variable(name) => name.to_string(),
+        Term::Abstraction { param, .. } => format!("λ{}", param),
+        Term::Application { .. } => "@".to_string(),
+      };
+      out.push_str(&format!("  n{id} [label=\"{label}\"];\n"));
+      let children: &[&Rc<Term<'_>>] = match term {
+        Term::Variable(_) => &[],
+        Term::Abstraction { body, .. } => &[body],
+        Term::Application { lhs, rhs } => &[lhs, rhs],
+      };
+      for child in children {
+        let child = go(child, out, next);
+        out.push_str(&format!("  n{id} -> n{child};\n"));
       }
+      id
     }
+    let mut out = String::from("digraph term {\n");
+    go(self, &mut out, &mut 0);
+    out.push_str("}\n");
+    out
+  }
+}
+
+impl<'a, 'src> fmt::Display for TermDisplay<'a, 'src> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    self.term.fmt_with(f, &self.options)
+  }
+}
+
+impl<'src> fmt::Display for Term<'src> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    self.fmt_with(f, &DisplayOptions::default())
   }
 }
 
@@ -51,17 +884,37 @@ mod tests {
   mod term {
     use super::*;
 
+    use crate::interpreter::Interpreter;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+
+    fn parse(input: &str) -> Term<'_> {
+      Parser::new(Lexer::new(input))
+        .parse()
+        .expect("test input should parse")
+    }
+
+    /// Displaying a term and parsing it back must give the same term
+    fn assert_round_trips(term: &Term<'_>) {
+      let printed = term.to_string();
+      let reparsed = parse(&printed);
+      assert!(
+        reparsed.alpha_eq(term),
+        "`{printed}` re-parsed as `{reparsed}`"
+      );
+    }
+
     #[test]
     fn display_variable() {
-      let term = Term::Variable("x");
+      let term = Term::Variable("x".into());
       assert_eq!(format!("{}", term), "x");
     }
 
     #[test]
     fn display_abstraction() {
       let term = Term::Abstraction {
-        param: "x",
-        body: Box::new(Term::Variable("x")),
+        param: "x".into(),
+        body: Rc::new(Term::Variable("x".into())),
       };
       assert_eq!(format!("{}", term), "λx. x");
     }
@@ -69,8 +922,8 @@ mod tests {
     #[test]
     fn display_application() {
       let term = Term::Application {
-        lhs: Box::new(Term::Variable("x")),
-        rhs: Box::new(Term::Variable("y")),
+        lhs: Rc::new(Term::Variable("x".into())),
+        rhs: Rc::new(Term::Variable("y".into())),
       };
       assert_eq!(format!("{}", term), "x y");
     }
@@ -78,16 +931,928 @@ mod tests {
     #[test]
     fn display_nested() {
       let term = Term::Application {
-        lhs: Box::new(Term::Abstraction {
-          param: "x",
-          body: Box::new(Term::Variable("x")),
+        lhs: Rc::new(Term::Abstraction {
+          param: "x".into(),
+          body: Rc::new(Term::Variable("x".into())),
         }),
-        rhs: Box::new(Term::Application {
-          lhs: Box::new(Term::Variable("y")),
-          rhs: Box::new(Term::Variable("z")),
+        rhs: Rc::new(Term::Application {
+          lhs: Rc::new(Term::Variable("y".into())),
+          rhs: Rc::new(Term::Variable("z".into())),
         }),
       };
       assert_eq!(format!("{}", term), "(λx. x) (y z)");
     }
+
+    #[test]
+    fn display_abstraction_body_unparenthesized() {
+      // λx. λy. x y
+      let term = Term::Abstraction {
+        param: "x".into(),
+        body: Rc::new(Term::Abstraction {
+          param: "y".into(),
+          body: Rc::new(Term::Application {
+            lhs: Rc::new(Term::Variable("x".into())),
+            rhs: Rc::new(Term::Variable("y".into())),
+          }),
+        }),
+      };
+      assert_eq!(format!("{}", term), "λx. λy. x y");
+    }
+
+    #[test]
+    fn display_application_left_associative() {
+      // (x y) z
+      let term = Term::Application {
+        lhs: Rc::new(Term::Application {
+          lhs: Rc::new(Term::Variable("x".into())),
+          rhs: Rc::new(Term::Variable("y".into())),
+        }),
+        rhs: Rc::new(Term::Variable("z".into())),
+      };
+      assert_eq!(format!("{}", term), "x y z");
+    }
+
+    #[test]
+    fn display_application_right_nested() {
+      // x (y z)
+      let term = Term::Application {
+        lhs: Rc::new(Term::Variable("x".into())),
+        rhs: Rc::new(Term::Application {
+          lhs: Rc::new(Term::Variable("y".into())),
+          rhs: Rc::new(Term::Variable("z".into())),
+        }),
+      };
+      assert_eq!(format!("{}", term), "x (y z)");
+    }
+
+    #[test]
+    fn display_abstraction_head_of_spine() {
+      // ((λx. x) y) (λz. z)
+      let term = Term::Application {
+        lhs: Rc::new(Term::Application {
+          lhs: Rc::new(Term::Abstraction {
+            param: "x".into(),
+            body: Rc::new(Term::Variable("x".into())),
+          }),
+          rhs: Rc::new(Term::Variable("y".into())),
+        }),
+        rhs: Rc::new(Term::Abstraction {
+          param: "z".into(),
+          body: Rc::new(Term::Variable("z".into())),
+        }),
+      };
+      assert_eq!(format!("{}", term), "(λx. x) y (λz. z)");
+    }
+
+    #[test]
+    fn display_abstraction_body_vs_head() {
+      let cases = [
+        // as a body, no parens however deep
+        ("λx. (λy. (λz. z))", "λx. λy. λz. z"),
+        ("λf. (λx. (f x))", "λf. λx. f x"),
+        // as the head of an application inside a body, parens are required
+        ("λx. ((λy. y) x)", "λx. (λy. y) x"),
+        ("λx. ((λy. y) x) z", "λx. (λy. y) x z"),
+        // as an argument, always parenthesized, even in last position
+        ("f (λx. x)", "f (λx. x)"),
+        ("(f (λx. x)) y", "f (λx. x) y"),
+      ];
+      for (input, expected) in cases {
+        let term = parse(input);
+        assert_eq!(term.to_string(), expected);
+        assert_round_trips(&term);
+      }
+    }
+
+    #[test]
+    fn free_variables_excludes_bound() {
+      // λx. x y
+      let term = Term::Abstraction {
+        param: "x".into(),
+        body: Rc::new(Term::Application {
+          lhs: Rc::new(Term::Variable("x".into())),
+          rhs: Rc::new(Term::Variable("y".into())),
+        }),
+      };
+      assert_eq!(term.free_variables(), HashSet::from(["y"]));
+    }
+
+    #[test]
+    fn is_closed() {
+      assert!(parse("λx. x").is_closed());
+      assert!(parse("λf. λx. f (f x)").is_closed());
+      assert!(!parse("y").is_closed());
+      assert!(!parse("λx. x y").is_closed());
+      // bound in one place, free in another
+      assert!(!parse("(λx. x) x").is_closed());
+    }
+
+    #[test]
+    fn is_combinator() {
+      assert!(parse("λx. x").is_combinator());
+      assert!(parse("λx. λy. x").is_combinator());
+      assert!(!parse("λx. y").is_combinator());
+      assert!(!parse("x").is_combinator());
+      // closed, but not an abstraction
+      assert!(!parse("(λx. x) (λx. x)").is_combinator());
+    }
+
+    #[test]
+    fn alpha_eq_renamed_binders() {
+      assert!(parse("λx. x").alpha_eq(&parse("λy. y")));
+      assert!(parse("λx. λy. x y").alpha_eq(&parse("λa. λb. a b")));
+    }
+
+    #[test]
+    fn alpha_eq_distinguishes_binding_structure() {
+      assert!(!parse("λx. λy. x").alpha_eq(&parse("λx. λy. y")));
+      // shadowing: the inner binder wins
+      assert!(parse("λx. λx. x").alpha_eq(&parse("λa. λb. b")));
+      assert!(!parse("λx. λx. x").alpha_eq(&parse("λa. λb. a")));
+    }
+
+    #[test]
+    fn alpha_eq_free_variables_by_name() {
+      assert!(parse("x").alpha_eq(&parse("x")));
+      assert!(!parse("x").alpha_eq(&parse("y")));
+      // a bound variable is never equal to a free one
+      assert!(!parse("λx. y").alpha_eq(&parse("λy. y")));
+    }
+
+    #[test]
+    fn round_trip_boolean_combinators() {
+      let and = "λp. λq. p q p";
+      let or = "λp. λq. p p q";
+      let not = "λp. p (λx. λy. y) (λx. λy. x)";
+      let t = "λx. λy. x";
+      let f = "λx. λy. y";
+      for input in [and, or, not, t, f] {
+        assert_round_trips(&parse(input));
+      }
+      assert_round_trips(&parse(&format!("({and}) ({t}) ({f})")));
+      assert_round_trips(&parse(&format!("({or}) ({f}) ({t})")));
+      assert_round_trips(&parse(&format!("({not}) ({t})")));
+    }
+
+    #[test]
+    fn round_trip_nested_parentheses() {
+      for input in [
+        "x (y z)",
+        "(λx. x) (λy. y) z",
+        "λx. (λy. y) x",
+        "x (λy. y) z",
+      ] {
+        assert_round_trips(&parse(input));
+      }
+    }
+
+    #[test]
+    fn round_trip_escaped_keywords() {
+      let term = Term::abs("let", Term::app(Term::var("let"), Term::var("in")));
+      assert_eq!(term.to_string(), "λ`let`. `let` `in`");
+      assert_round_trips(&term);
+      assert_round_trips(&parse("let x = `in` in x"));
+    }
+
+    #[test]
+    fn substitute_variable() {
+      // x[x := y] is y, while z[x := y] is left alone
+      assert_eq!(
+        Term::var("x").substitute("x", &Term::var("y")),
+        Term::var("y")
+      );
+      assert_eq!(
+        Term::var("z").substitute("x", &Term::var("y")),
+        Term::var("z")
+      );
+    }
+
+    #[test]
+    fn substitute_abstraction_no_capture() {
+      // (λx. x)[y := z] is λx. x, and (λx. x)[x := z] too since `x` is bound
+      let term = parse("λx. x");
+      assert_eq!(term.substitute("y", &Term::var("z")), term);
+      assert_eq!(term.substitute("x", &Term::var("z")), term);
+    }
+
+    #[test]
+    fn substitute_abstraction_capture_internal() {
+      // (λx. y)[y := z] is λx. z
+      assert_eq!(
+        parse("λx. y").substitute("y", &Term::var("z")),
+        parse("λx. z")
+      );
+    }
+
+    #[test]
+    fn substitute_abstraction_avoids_capture() {
+      // (λy. x)[x := y] renames the binder, giving λy'. y
+      assert_eq!(
+        parse("λy. x").substitute("x", &Term::var("y")),
+        Term::abs("y'", Term::var("y"))
+      );
+      // skipping any primed name already in use
+      assert_eq!(
+        Term::abs("y", Term::app(Term::var("x"), Term::var("y'"))).substitute("x", &Term::var("y")),
+        Term::abs("y''", Term::app(Term::var("y"), Term::var("y'")))
+      );
+    }
+
+    #[test]
+    fn substitute_application() {
+      // (x y)[x := z] is z y
+      assert_eq!(parse("x y").substitute("x", &Term::var("z")), parse("z y"));
+      assert_eq!(
+        parse("x (λx. x) x").substitute("x", &parse("λa. a")),
+        parse("(λa. a) (λx. x) (λa. a)")
+      );
+    }
+
+    #[test]
+    fn substitute_shares_untouched_subtrees() {
+      let term = parse("(x y) (λz. z w)");
+      let Term::Application { lhs, rhs } = &term else {
+        unreachable!()
+      };
+      let subs = term.substitute_with("x", &Term::var("a"), &mut FreshNamer::new(), &mut 0);
+      assert_eq!(subs, parse("(a y) (λz. z w)"));
+      let Term::Application {
+        lhs: new_lhs,
+        rhs: new_rhs,
+      } = &subs
+      else {
+        unreachable!()
+      };
+      // the argument never mentions `x`, so it's the very same allocation
+      assert!(Rc::ptr_eq(rhs, new_rhs));
+      assert!(!Rc::ptr_eq(lhs, new_lhs));
+    }
+
+    #[test]
+    fn substitute_unchanged_when_absent() {
+      let term = parse("λy. y (z y)");
+      let subs = term.substitute_with("x", &Term::var("a"), &mut FreshNamer::new(), &mut 0);
+      assert_eq!(subs, term);
+      let (Term::Abstraction { body, .. }, Term::Abstraction { body: new_body, .. }) =
+        (&term, &subs)
+      else {
+        unreachable!()
+      };
+      assert!(Rc::ptr_eq(body, new_body));
+    }
+
+    #[test]
+    fn to_owned_term_outlives_source() {
+      let owned: OwnedTerm = {
+        let source = String::from("(λx. λy. x) a b");
+        parse(&source).to_owned_term()
+      };
+      fn owns_names(term: &Term<'_>) -> bool {
+        match term {
+          Term::Variable(name) => matches!(name, Cow::Owned(_)),
+          Term::Abstraction { param, body } => matches!(param, Cow::Owned(_)) && owns_names(body),
+          Term::Application { lhs, rhs } => owns_names(lhs) && owns_names(rhs),
+        }
+      }
+      assert!(owns_names(&owned));
+      assert_eq!(owned.to_string(), "(λx. λy. x) a b");
+      let result = Interpreter::new().evaluate(&owned);
+      assert_eq!(result, Term::var("a"));
+    }
+
+    #[test]
+    fn diff_reports_path() {
+      let diff = parse("λx. x y").diff(&parse("λx. x z")).unwrap();
+      assert_eq!(diff.path, [Step::Body, Step::Rhs]);
+      assert_eq!((diff.left, diff.right), (Term::var("y"), Term::var("z")));
+      let diff = parse("f (a b) c").diff(&parse("f (a d) c")).unwrap();
+      assert_eq!(diff.path, [Step::Lhs, Step::Rhs, Step::Rhs]);
+      assert_eq!(diff.to_string(), "at lhs.rhs.rhs: `b` vs `d`");
+    }
+
+    #[test]
+    fn at_follows_path() {
+      let term = parse("λx. x (f y)");
+      assert_eq!(term.at(&[]), Some(&term));
+      assert_eq!(
+        term.at(&[Step::Body, Step::Rhs, Step::Lhs]),
+        Some(&Term::var("f"))
+      );
+      assert_eq!(term.at(&[Step::Lhs]), None);
+      let diff = term.diff(&parse("λx. x (f z)")).unwrap();
+      assert_eq!(term.at(&diff.path), Some(&diff.left));
+    }
+
+    #[test]
+    fn diff_identical_terms() {
+      let term = parse("λf. λx. f (f x) ((λy. y) x)");
+      assert_eq!(term.diff(&term.clone()), None);
+      // binder names count, unlike with `alpha_eq`
+      let diff = parse("λx. x").diff(&parse("λy. y")).unwrap();
+      assert_eq!(diff.to_string(), "at root: `λx. x` vs `λy. y`");
+    }
+
+    #[test]
+    fn as_church_bool() {
+      assert_eq!(parse("λx. λy. x").as_church_bool(), Some(true));
+      assert_eq!(parse("λa. λb. b").as_church_bool(), Some(false));
+      assert_eq!(parse("λx. λx. x").as_church_bool(), Some(false));
+      assert_eq!(parse("λx. λy. z").as_church_bool(), None);
+      assert_eq!(parse("λx. x").as_church_bool(), None);
+    }
+
+    #[test]
+    fn as_church_numeral() {
+      assert_eq!(parse("λf. λx. x").as_church_numeral(), Some(0));
+      assert_eq!(parse("λf. λx. f x").as_church_numeral(), Some(1));
+      assert_eq!(parse("λs. λz. s (s (s z))").as_church_numeral(), Some(3));
+      assert_eq!(parse("λf. λf. f").as_church_numeral(), Some(0));
+      assert_eq!(parse("λf. λx. f f x").as_church_numeral(), None);
+      assert_eq!(parse("λf. λx. x f").as_church_numeral(), None);
+      assert_eq!(parse("λf. λx. f (f y)").as_church_numeral(), None);
+    }
+
+    #[test]
+    fn builders_match_manual_construction() {
+      // λp. λq. p q p
+      let manual = Term::Abstraction {
+        param: "p".into(),
+        body: Rc::new(Term::Abstraction {
+          param: "q".into(),
+          body: Rc::new(Term::Application {
+            lhs: Rc::new(Term::Application {
+              lhs: Rc::new(Term::Variable("p".into())),
+              rhs: Rc::new(Term::Variable("q".into())),
+            }),
+            rhs: Rc::new(Term::Variable("p".into())),
+          }),
+        }),
+      };
+      let built = Term::abs(
+        "p",
+        Term::abs(
+          "q",
+          Term::app(Term::app(Term::var("p"), Term::var("q")), Term::var("p")),
+        ),
+      );
+      assert_eq!(built, manual);
+    }
+
+    #[test]
+    fn apps_left_folds() {
+      let built = Term::apps([Term::var("p"), Term::var("q"), Term::var("p")]);
+      assert_eq!(
+        built,
+        Some(Term::app(
+          Term::app(Term::var("p"), Term::var("q")),
+          Term::var("p")
+        ))
+      );
+      assert_eq!(Term::apps([Term::var("x")]), Some(Term::var("x")));
+      assert_eq!(Term::apps([]), None);
+    }
+
+    #[test]
+    fn children_of_each_variant() {
+      assert!(Term::var("x").children().is_empty());
+      let term = parse("λx. x y");
+      assert_eq!(term.children(), [&parse("x y")]);
+      let term = parse("(λx. x) y");
+      assert_eq!(term.children(), [&parse("λx. x"), &Term::var("y")]);
+    }
+
+    #[test]
+    fn children_breadth_first() {
+      use std::collections::VecDeque;
+
+      let term = parse("(λx. x) (f y)");
+      let mut queue = VecDeque::from([&term]);
+      let mut sizes = Vec::new();
+      while let Some(next) = queue.pop_front() {
+        sizes.push(next.size());
+        queue.extend(next.children());
+      }
+      assert_eq!(sizes, [6, 2, 3, 1, 1, 1]);
+      assert_eq!(sizes.len(), term.size());
+    }
+
+    #[test]
+    fn spine_flattens_applications() {
+      let term = parse("x y z");
+      let (head, args) = term.spine();
+      assert_eq!(head, &Term::var("x"));
+      assert_eq!(args, vec![&Term::var("y"), &Term::var("z")]);
+
+      // only the left spine is peeled
+      let term = parse("(λf. f) (a b) c");
+      let (head, args) = term.spine();
+      assert_eq!(head, &parse("λf. f"));
+      assert_eq!(args, vec![&parse("a b"), &Term::var("c")]);
+
+      let term = Term::var("x");
+      assert_eq!(term.spine(), (&term, vec![]));
+    }
+
+    #[test]
+    fn from_spine_left_folds() {
+      assert_eq!(
+        Term::from_spine(Term::var("x"), [Term::var("y"), Term::var("z")]),
+        Term::Application {
+          lhs: Rc::new(Term::Application {
+            lhs: Rc::new(Term::var("x")),
+            rhs: Rc::new(Term::var("y")),
+          }),
+          rhs: Rc::new(Term::var("z")),
+        }
+      );
+    }
+
+    #[test]
+    fn lambda_chain_curries() {
+      let body = parse("x y");
+      assert_eq!(
+        Term::lambda_chain(["x", "y"], body.clone()),
+        Term::abs("x", Term::abs("y", body.clone()))
+      );
+      assert_eq!(
+        Term::lambda_chain(["x", "y"], body.clone()),
+        parse("λx y. x y")
+      );
+      assert_eq!(Term::lambda_chain(Vec::<&str>::new(), body.clone()), body);
+    }
+
+    #[test]
+    fn from_spine_rebuilds() {
+      for src in ["x y z", "(λf. f) (a b) c", "x", "λx. x y"] {
+        let term = parse(src);
+        let (head, args) = term.spine();
+        let rebuilt = Term::from_spine(head.clone(), args.into_iter().cloned());
+        assert_eq!(rebuilt, term);
+      }
+    }
+
+    #[test]
+    fn for_each_spine_arg_matches_spine() {
+      let mut args = Vec::new();
+      for src in ["x y z", "(λf. f) (a b) c", "x", "λx. x y"] {
+        let term = parse(src);
+        args.clear();
+        term.for_each_spine_arg(|arg| args.push(arg.clone()));
+        let (_, expected) = term.spine();
+        assert_eq!(args.iter().collect::<Vec<_>>(), expected);
+      }
+    }
+
+    #[test]
+    fn for_each_free_var_matches_free_variables() {
+      let mut names = Vec::new();
+      for src in ["x", "λx. x", "λx. x y", "(λx. x) x z", "λf. f (λy. f y w)"] {
+        let term = parse(src);
+        names.clear();
+        term.for_each_free_var(|name| names.push(name.to_string()));
+        let visited: HashSet<&str> = names.iter().map(String::as_str).collect();
+        assert_eq!(visited, term.free_variables());
+      }
+      // once per occurrence
+      names.clear();
+      parse("x x").for_each_free_var(|name| names.push(name.to_string()));
+      assert_eq!(names, ["x", "x"]);
+    }
+
+    #[test]
+    fn rename_everything() {
+      let x_to_z = |name: &str| (name == "x").then_some("z");
+      assert_eq!(parse("λx. x y").rename(x_to_z), parse("λz. z y"));
+      assert_eq!(parse("x (λw. x w)").rename(x_to_z), parse("z (λw. z w)"));
+      assert_eq!(parse("a b").rename(x_to_z), parse("a b"));
+    }
+
+    #[test]
+    fn rename_free_only() {
+      let x_to_z = |name: &str| (name == "x").then_some("z");
+      // the `x` here is bound, so there's nothing free to rename
+      assert_eq!(parse("λx. x y").rename_free(x_to_z), parse("λx. x y"));
+      assert_eq!(parse("x (λx. x)").rename_free(x_to_z), parse("z (λx. x)"));
+      assert_eq!(parse("λy. x y").rename_free(x_to_z), parse("λy. z y"));
+    }
+
+    #[test]
+    fn restore_names_when_safe() {
+      let (x2, y1, y2, z1) = (
+        Term::var("x''"),
+        Term::var("y'"),
+        Term::var("y''"),
+        Term::var("z'"),
+      );
+      assert_eq!(Term::abs("x''", x2).restore_names(), parse("λx. x"));
+      assert_eq!(
+        Term::abs("y'", Term::abs("z'", Term::app(y1.clone(), z1))).restore_names(),
+        parse("λy. λz. y z")
+      );
+      // `y` is still free, but `y''` can at least drop to `y'`
+      let still_free = Term::abs("y'", Term::var("y"));
+      assert_eq!(still_free.restore_names(), still_free);
+      assert_eq!(
+        Term::abs("y''", Term::app(Term::var("y"), y2)).restore_names(),
+        Term::abs("y'", Term::app(Term::var("y"), y1.clone()))
+      );
+      // renaming the outer binder would be captured by the inner one
+      let captured = Term::abs("y'", Term::abs("y", y1.clone()));
+      assert_eq!(captured.restore_names(), captured);
+      // unless the inner one doesn't use it
+      assert_eq!(
+        Term::abs("y'", Term::app(y1, parse("λy. y"))).restore_names(),
+        parse("λy. y (λy. y)")
+      );
+      // no primes, nothing to do
+      assert_eq!(parse("λx. x y").restore_names(), parse("λx. x y"));
+    }
+
+    #[test]
+    fn to_dot_nodes_and_edges() {
+      let dot = parse("λx. x y").to_dot();
+      assert!(dot.starts_with("digraph term {\n") && dot.ends_with("}\n"));
+      for label in ["\"λx\"", "\"@\"", "\"x\"", "\"y\""] {
+        assert_eq!(dot.matches(label).count(), 1, "{label} in {dot}");
+      }
+      assert_eq!(dot.matches(" -> ").count(), 3);
+      assert_eq!(
+        dot,
+        "digraph term {\n  n0 [label=\"λx\"];\n  n1 [label=\"@\"];\n  n2 [label=\"x\"];\n  n1 -> n2;\n  n3 [label=\"y\"];\n  n1 -> n3;\n  n0 -> n1;\n}\n"
+      );
+    }
+
+    #[test]
+    fn to_dot_repeated_subterms() {
+      // both sides are the same subterm, but still get a node each
+      let x = Term::var("x");
+      let term = Term::app(x.clone(), x);
+      let dot = term.to_dot();
+      assert_eq!(dot.matches("[label=\"x\"]").count(), 2);
+      assert!(dot.contains("n0 -> n1;") && dot.contains("n0 -> n2;"));
+    }
+
+    #[test]
+    fn normal_form_predicates() {
+      let identity = parse("λx. x");
+      assert!(identity.is_normal_form() && !identity.is_redex());
+
+      let redex = parse("(λx. x) y");
+      assert!(redex.is_redex() && !redex.is_normal_form());
+
+      // the redex is tucked away in an argument
+      let nested = parse("x ((λy. y) z)");
+      assert!(!nested.is_redex() && !nested.is_normal_form());
+      assert!(nested.is_head_normal_form());
+
+      // and under a binder
+      let under = parse("λf. (λy. y) f");
+      assert!(!under.is_normal_form() && !under.is_head_normal_form());
+      assert!(parse("λf. λx. f (f x)").is_head_normal_form());
+    }
+
+    #[test]
+    fn ordering_is_deterministic() {
+      let mut terms: Vec<_> = ["x y", "λy. y", "b", "x (y z)", "λx. x", "a", "x y z"]
+        .into_iter()
+        .map(parse)
+        .collect();
+      terms.sort();
+      let sorted: Vec<_> = terms.iter().map(|term| term.to_string()).collect();
+      assert_eq!(
+        sorted,
+        // a variable on the left sorts before an application there
+        ["a", "b", "λx. x", "λy. y", "x y", "x (y z)", "x y z"]
+      );
+    }
+
+    #[test]
+    fn ordering_sees_binder_names() {
+      // α-equivalent, but still ordered apart
+      assert!(parse("λa. a") < parse("λb. b"));
+      assert_eq!(
+        parse("λa. a")
+          .canonicalize()
+          .cmp(&parse("λb. b").canonicalize()),
+        std::cmp::Ordering::Equal
+      );
+    }
+
+    #[test]
+    fn canonicalize_alpha_equivalent() {
+      assert_eq!(
+        parse("λx. λy. x y").canonicalize(),
+        parse("λa. λb. a b").canonicalize()
+      );
+      assert_ne!(
+        parse("λx. λy. x").canonicalize(),
+        parse("λx. λy. y").canonicalize()
+      );
+    }
+
+    #[test]
+    fn canonicalize_avoids_free_names() {
+      // the binder can't become `v0`, that would capture the free `v0`
+      assert_eq!(parse("λx. v0 x").canonicalize(), parse("λv1. v0 v1"));
+    }
+
+    #[test]
+    fn canonicalize_deduplicates() {
+      let terms = [
+        "λx. x y",
+        "λz. z y",
+        "λx. x z",
+        "(λa. a) (λb. b)",
+        "(λx. x) (λx. x)",
+      ];
+      let unique: HashSet<_> = terms.iter().map(|src| parse(src).canonicalize()).collect();
+      assert_eq!(unique.len(), 3);
+      // and canonical forms agree with α-equivalence
+      assert!(parse("λa. λb. b a").alpha_eq(&parse("λa. λb. b a").canonicalize()));
+    }
+
+    #[test]
+    fn display_lambda_glyph() {
+      let term = Term::abs("x", Term::var("x"));
+      assert_eq!(format!("{}", term), "λx. x");
+      assert_eq!(format!("{}", term.display_ascii()), "\\x. x");
+      let options = DisplayOptions {
+        lambda: '^',
+        ..DisplayOptions::default()
+      };
+      assert_eq!(format!("{}", term.display_with(options)), "^x. x");
+    }
+
+    #[test]
+    fn display_numerals() {
+      use crate::prelude::{numeral, succ};
+
+      assert_eq!(numeral(0).display_numerals().to_string(), "0");
+      assert_eq!(numeral(2).display_numerals().to_string(), "2");
+      // not numeral-shaped, so rendered as usual
+      let term = parse("λf. λx. x f");
+      assert_eq!(term.display_numerals().to_string(), "λf. λx. x f");
+      // numerals nested inside other terms are atoms
+      let term = Term::apps([succ(), numeral(2)]).unwrap();
+      assert_eq!(
+        term.display_numerals().to_string(),
+        "(λn. λf. λx. f (n f x)) 2"
+      );
+      let term = Term::app(numeral(3), Term::var("g"));
+      assert_eq!(term.display_numerals().to_string(), "3 g");
+      // and off by default
+      assert_eq!(numeral(1).to_string(), "λf. λx. f x");
+    }
+
+    #[test]
+    fn display_collapsed() {
+      assert_eq!(
+        parse("λx. λy. x").display_collapsed().to_string(),
+        "λx y. x"
+      );
+      let term = parse("λf. (λx. λy. x) (λa. λb. λc. c) f");
+      assert_eq!(
+        term.display_collapsed().to_string(),
+        "λf. (λx y. x) (λa b c. c) f"
+      );
+      let options = DisplayOptions {
+        numerals: true,
+        collapse_binders: true,
+        ..DisplayOptions::ascii()
+      };
+      let term = Term::abs("n", crate::prelude::numeral(2));
+      assert_eq!(term.display_with(options).to_string(), "\\n. 2");
+      // and off by default
+      assert_eq!(parse("λx. λy. x").to_string(), "λx. λy. x");
+    }
+
+    #[test]
+    fn round_trip_collapsed() {
+      for src in ["λx. λy. λz. x z (y z)", "(λx. λy. x) (λa. a)", "λx. λx. x"] {
+        let term = parse(src);
+        let printed = term.display_collapsed().to_string();
+        assert_eq!(parse(&printed), term, "{printed}");
+      }
+    }
+
+    #[test]
+    fn display_ascii_nested() {
+      let term = parse("(λx. λy. x) (λz. z)");
+      assert_eq!(term.display_ascii().to_string(), "(\\x. \\y. x) (\\z. z)");
+      // and it still parses back to the same thing
+      assert_eq!(parse(&term.display_ascii().to_string()), term);
+    }
+
+    #[test]
+    fn size_and_depth() {
+      let variable = Term::var("x");
+      assert_eq!((variable.size(), variable.depth()), (1, 1));
+
+      let term = parse("λx. x y");
+      assert_eq!(term.size(), 4);
+      assert_eq!(term.depth(), 3);
+
+      let and = parse("λp. λq. p q p");
+      assert_eq!(and.size(), 7);
+      assert_eq!(and.depth(), 5);
+
+      // depth follows the deeper side only
+      let lopsided = parse("x (y (z w))");
+      assert_eq!(lopsided.size(), 7);
+      assert_eq!(lopsided.depth(), 4);
+    }
+
+    #[test]
+    fn bound_variables() {
+      let term = parse("(λx. λy. x z) (λw. w)");
+      assert_eq!(term.bound_variables(), HashSet::from(["x", "y", "w"]));
+      assert!(parse("x y").bound_variables().is_empty());
+    }
+
+    #[test]
+    fn has_shadowing() {
+      assert!(parse("λx. λx. x").has_shadowing());
+      assert!(parse("λx. y (λy. λx. x)").has_shadowing());
+      // the same name on separate paths isn't shadowing
+      assert!(!parse("(λx. x) (λx. x)").has_shadowing());
+      assert!(!parse("λx. λy. x y").has_shadowing());
+    }
+  }
+
+  mod substitution {
+    use super::*;
+
+    use crate::rng::Rng;
+
+    /// Few names, so binders clash with the value's free variables often
+    const NAMES: &[&str] = &["x", "y", "z"];
+
+    fn random_term(rng: &mut Rng, depth: usize) -> Term<'static> {
+      let name = NAMES[rng.below(NAMES.len())];
+      match rng.below(if depth == 0 { 1 } else { 3 }) {
+        0 => Term::var(name),
+        1 => Term::abs(name, random_term(rng, depth - 1)),
+        _ => Term::app(random_term(rng, depth - 1), random_term(rng, depth - 1)),
+      }
+    }
+
+    /// The textbook version, rebuilding every node whether anything beneath it
+    /// changed or not
+    fn naive_substitute<'src>(
+      term: &Term<'src>,
+      var: &str,
+      value: &Term<'src>,
+      namer: &mut FreshNamer,
+    ) -> Term<'src> {
+      match term {
+        Term::Variable(name) if name == var => value.clone(),
+        Term::Variable(name) => Term::Variable(name.clone()),
+        // the binder shadows `var`, so there's nothing to replace beneath it
+        Term::Abstraction { param, .. } if param == var => term.clone(),
+        Term::Abstraction { param, body } => {
+          let free = value.free_variables();
+          if free.contains(param.as_ref()) && body.free_variables().contains(var) {
+            let mut used = body.free_variables();
+            used.extend(&free);
+            used.insert(var);
+            let fresh = namer.fresh(param, &used);
+            let renamed = naive_substitute(body, param, &Term::var(fresh.clone()), namer);
+            Term::abs(fresh, naive_substitute(&renamed, var, value, namer))
+          } else {
+            Term::Abstraction {
+              param: param.clone(),
+              body: Rc::new(naive_substitute(body, var, value, namer)),
+            }
+          }
+        }
+        Term::Application { lhs, rhs } => Term::app(
+          naive_substitute(lhs, var, value, namer),
+          naive_substitute(rhs, var, value, namer),
+        ),
+      }
+    }
+
+    #[test]
+    fn sharing_matches_naive() {
+      let mut rng = Rng(0x0dd_ba11_cafe_f00d);
+      for _ in 0..2_000 {
+        let term = random_term(&mut rng, 6);
+        let value = random_term(&mut rng, 3);
+        let var = NAMES[rng.below(NAMES.len())];
+        let shared = term.substitute_with(var, &value, &mut FreshNamer::new(), &mut 0);
+        let naive = naive_substitute(&term, var, &value, &mut FreshNamer::new());
+        assert_eq!(
+          shared, naive,
+          "substituting `{value}` for `{var}` in `{term}`"
+        );
+      }
+    }
+
+    /// Smaller terms to retry a failing case with, each one step simpler
+    fn shrink(term: &Term<'static>) -> Vec<Term<'static>> {
+      match term {
+        Term::Variable(_) => Vec::new(),
+        Term::Abstraction { param, body } => {
+          let mut smaller = vec![Term::clone(body)];
+          smaller.extend(
+            shrink(body)
+              .into_iter()
+              .map(|body| Term::abs(param.clone(), body)),
+          );
+          smaller
+        }
+        Term::Application { lhs, rhs } => {
+          let mut smaller = vec![Term::clone(lhs), Term::clone(rhs)];
+          smaller.extend(
+            shrink(lhs)
+              .into_iter()
+              .map(|lhs| Term::app(lhs, Term::clone(rhs))),
+          );
+          smaller.extend(
+            shrink(rhs)
+              .into_iter()
+              .map(|rhs| Term::app(Term::clone(lhs), rhs)),
+          );
+          smaller
+        }
+      }
+    }
+
+    /// Greedily shrink a failing case for as long as it keeps failing
+    fn minimize(
+      mut case: (Term<'static>, Term<'static>),
+      fails: impl Fn(&Term<'static>, &Term<'static>) -> bool,
+    ) -> (Term<'static>, Term<'static>) {
+      'smaller: loop {
+        let (term, value) = &case;
+        let candidates = shrink(term)
+          .into_iter()
+          .map(|term| (term, value.clone()))
+          .chain(shrink(value).into_iter().map(|value| (term.clone(), value)));
+        for candidate in candidates {
+          if fails(&candidate.0, &candidate.1) {
+            case = candidate;
+            continue 'smaller;
+          }
+        }
+        return case;
+      }
+    }
+
+    /// Check a property of substituting a random value for `x` in a random term,
+    /// over many such cases, panicking with the smallest failing one found
+    fn check(property: impl Fn(&Term<'static>, &Term<'static>) -> bool) {
+      let mut rng = Rng(0x5ab5_717a_7e00_0001);
+      for _ in 0..2_000 {
+        let term = random_term(&mut rng, 6);
+        let value = random_term(&mut rng, 3);
+        if !property(&term, &value) {
+          let (term, value) = minimize((term, value), |term, value| !property(term, value));
+          panic!("fails substituting `{value}` for `x` in `{term}`");
+        }
+      }
+    }
+
+    #[test]
+    fn free_variables_as_expected() {
+      check(|term, value| {
+        let result = term.substitute("x", value);
+        let mut expected = term.free_variables();
+        if expected.remove("x") {
+          expected.extend(value.free_variables());
+        }
+        result.free_variables() == expected
+      });
+    }
+
+    #[test]
+    fn binder_names_dont_matter() {
+      // renaming every binder first can't change the result, beyond α
+      check(|term, value| {
+        let renamed = term.canonicalize();
+        term
+          .substitute("x", value)
+          .alpha_eq(&renamed.substitute("x", value))
+      });
+    }
+
+    #[test]
+    fn minimize_shrinks() {
+      // a made up property failing whenever `y` is free, to check the shrinking
+      let fails = |term: &Term<'static>, _: &Term<'static>| term.free_variables().contains("y");
+      let term = Term::abs(
+        "x",
+        Term::from_spine(
+          Term::var("x"),
+          [
+            Term::abs("z", Term::app(Term::var("z"), Term::var("y"))),
+            Term::var("x"),
+          ],
+        ),
+      );
+      let value = Term::app(Term::var("a"), Term::var("b"));
+      let (term, value) = minimize((term, value), fails);
+      assert_eq!((term, value), (Term::var("y"), Term::var("a")));
+    }
   }
 }
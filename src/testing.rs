@@ -0,0 +1,53 @@
+//!
+//! Assertions for writing evaluation tests in terms of source text rather than
+//! hand-built syntax trees
+//!
+
+use crate::api::parse_str;
+use crate::interpreter::Interpreter;
+use crate::term::Evaluate;
+
+/// Assert that `input` evaluates (with a default `Interpreter`) to something
+/// α-equivalent to `expected`
+///
+/// # Panics
+///
+/// If either side fails to parse, or the evaluated term isn't α-equivalent to
+/// `expected`, with both terms in the message
+pub fn assert_evaluates_to(input: &str, expected: &str) {
+  let term = parse_str(input).unwrap_or_else(|e| panic!("`{input}` failed to parse: {e}"));
+  let expected_term =
+    parse_str(expected).unwrap_or_else(|e| panic!("`{expected}` failed to parse: {e}"));
+  let actual = Interpreter::new().evaluate(&term);
+  assert!(
+    actual.alpha_eq(&expected_term),
+    "`{input}` evaluated to `{actual}`, expected `{expected_term}` (up to α)"
+  );
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  mod testing {
+    use super::*;
+
+    #[test]
+    fn evaluates_to_up_to_alpha() {
+      assert_evaluates_to("(λx. x) y", "y");
+      assert_evaluates_to("(λx. λy. x) (λa. a)", "λy. λb. b");
+    }
+
+    #[test]
+    #[should_panic(expected = "`(λx. x) y` evaluated to `y`, expected `z` (up to α)")]
+    fn mismatch_shows_both_terms() {
+      assert_evaluates_to("(λx. x) y", "z");
+    }
+
+    #[test]
+    #[should_panic(expected = "failed to parse")]
+    fn unparsable_expectation() {
+      assert_evaluates_to("y", "λ. y");
+    }
+  }
+}
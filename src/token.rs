@@ -2,11 +2,198 @@
 //! Simplest available unit of the language, used to represent atoms in the grammar
 //!
 
+use std::fmt;
+
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum Token<'src> {
   LParen,             // '('
   RParen,             // ')'
-  Lambda,             // 'λ' or '\'
+  Lambda(char),       // 'λ' or '\', whichever was written
   Dot,                // '.'
+  Arrow,              // '->', alternative to '.'
+  Equals,             // '='
+  Semicolon,          // ';'
+  Comma,              // ',' between binder names
   Binding(&'src str), // some lowercase id
+  Number(u64),        // a run of ASCII digits, standing for a Church numeral
+  Let,                // 'let'
+  In,                 // 'in'
+  Invalid(char),      // anything the lexer couldn't make sense of, see `Lexer::spanned` for where
+  Eof,                // explicit end of input, never produced by the lexer
+  Trivia(&'src str),  // whitespace and comments, only kept by `Lexer::with_trivia`
+}
+
+/// Names the lexer reads as keywords rather than bindings, unless escaped as in
+/// `` `let` ``
+pub const KEYWORDS: &[&str] = &["let", "in"];
+
+/// Whether a name can only be written escaped
+pub fn is_keyword(name: &str) -> bool {
+  KEYWORDS.contains(&name)
+}
+
+/// Tokens print as they'd be written, so joining them with spaces gives source
+/// that lexes back to the same tokens
+/// - `Eof` prints as nothing, since it was never written
+impl<'src> fmt::Display for Token<'src> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Token::LParen => write!(f, "("),
+      Token::RParen => write!(f, ")"),
+      Token::Lambda(glyph) => write!(f, "{}", glyph),
+      Token::Dot => write!(f, "."),
+      Token::Arrow => write!(f, "->"),
+      Token::Equals => write!(f, "="),
+      Token::Semicolon => write!(f, ";"),
+      Token::Comma => write!(f, ","),
+      Token::Binding(name) if is_keyword(name) => write!(f, "`{}`", name),
+      Token::Binding(name) => write!(f, "{}", name),
+      Token::Number(n) => write!(f, "{}", n),
+      Token::Let => write!(f, "let"),
+      Token::In => write!(f, "in"),
+      Token::Invalid(c) => write!(f, "{}", c),
+      Token::Eof => Ok(()),
+      Token::Trivia(text) => write!(f, "{}", text),
+    }
+  }
+}
+
+/// A `Token` that owns its binding name, for when there's no source buffer to
+/// borrow from (see `lexer::OwnedLexer`)
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum OwnedToken {
+  LParen,
+  RParen,
+  Lambda(char),
+  Dot,
+  Arrow,
+  Equals,
+  Semicolon,
+  Comma,
+  Binding(String),
+  Number(u64),
+  Let,
+  In,
+  Invalid(char),
+  Eof,
+  Trivia(String),
+}
+
+impl OwnedToken {
+  /// Borrow as a regular token, e.g. to feed into a `Parser`
+  pub fn as_token(&self) -> Token<'_> {
+    match self {
+      OwnedToken::LParen => Token::LParen,
+      OwnedToken::RParen => Token::RParen,
+      OwnedToken::Lambda(glyph) => Token::Lambda(*glyph),
+      OwnedToken::Dot => Token::Dot,
+      OwnedToken::Arrow => Token::Arrow,
+      OwnedToken::Equals => Token::Equals,
+      OwnedToken::Semicolon => Token::Semicolon,
+      OwnedToken::Comma => Token::Comma,
+      OwnedToken::Binding(name) => Token::Binding(name),
+      OwnedToken::Number(n) => Token::Number(*n),
+      OwnedToken::Let => Token::Let,
+      OwnedToken::In => Token::In,
+      OwnedToken::Invalid(c) => Token::Invalid(*c),
+      OwnedToken::Eof => Token::Eof,
+      OwnedToken::Trivia(text) => Token::Trivia(text),
+    }
+  }
+}
+
+impl fmt::Display for OwnedToken {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    self.as_token().fmt(f)
+  }
+}
+
+impl<'src> From<Token<'src>> for OwnedToken {
+  fn from(token: Token<'src>) -> Self {
+    match token {
+      Token::LParen => OwnedToken::LParen,
+      Token::RParen => OwnedToken::RParen,
+      Token::Lambda(glyph) => OwnedToken::Lambda(glyph),
+      Token::Dot => OwnedToken::Dot,
+      Token::Arrow => OwnedToken::Arrow,
+      Token::Equals => OwnedToken::Equals,
+      Token::Semicolon => OwnedToken::Semicolon,
+      Token::Comma => OwnedToken::Comma,
+      Token::Binding(name) => OwnedToken::Binding(name.to_string()),
+      Token::Number(n) => OwnedToken::Number(n),
+      Token::Let => OwnedToken::Let,
+      Token::In => OwnedToken::In,
+      Token::Invalid(c) => OwnedToken::Invalid(c),
+      Token::Eof => OwnedToken::Eof,
+      Token::Trivia(text) => OwnedToken::Trivia(text.to_string()),
+    }
+  }
+}
+
+/// Byte range of the input some token was read from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+  pub start: usize,
+  pub end: usize,
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  mod token {
+    use super::*;
+
+    use crate::lexer::Lexer;
+
+    #[test]
+    fn display_as_written() {
+      let rendered: Vec<_> = [
+        Token::LParen,
+        Token::RParen,
+        Token::Lambda('λ'),
+        Token::Lambda('\\'),
+        Token::Dot,
+        Token::Arrow,
+        Token::Equals,
+        Token::Semicolon,
+        Token::Comma,
+        Token::Binding("x₁"),
+        Token::Number(42),
+        Token::Let,
+        Token::In,
+        Token::Binding("let"),
+        Token::Invalid('#'),
+        Token::Eof,
+      ]
+      .iter()
+      .map(Token::to_string)
+      .collect();
+      assert_eq!(
+        rendered,
+        [
+          "(", ")", "λ", "\\", ".", "->", "=", ";", ",", "x₁", "42", "let", "in", "`let`", "#", ""
+        ]
+      );
+      assert_eq!(OwnedToken::Binding("y".into()).to_string(), "y");
+    }
+
+    #[test]
+    fn display_round_trips() {
+      for src in [
+        "λx. x y",
+        "(\\f -> f f) (g = h;)",
+        "λa, b. a",
+        "let `in` = a in `in`",
+      ] {
+        let tokens = Lexer::new(src).tokenize();
+        let rendered = tokens
+          .iter()
+          .map(Token::to_string)
+          .collect::<Vec<_>>()
+          .join(" ");
+        assert_eq!(Lexer::new(&rendered).tokenize(), tokens);
+      }
+    }
+  }
 }
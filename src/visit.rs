@@ -0,0 +1,114 @@
+//!
+//! Generic traversal over `Term<'src>`, so analysis passes don't have to
+//! re-implement the recursion themselves
+//!
+
+use crate::term::Term;
+
+/// Hooks called for each kind of node while walking a term
+///
+/// Every method defaults to continuing the walk into the node's children, so an
+/// implementor only overrides what it cares about (calling `walk` on the children
+/// if it still wants to descend)
+pub trait Visitor<'src> {
+  fn visit_variable(&mut self, _name: &str) {}
+
+  fn visit_abstraction(&mut self, _param: &str, body: &Term<'src>) {
+    walk(self, body);
+  }
+
+  fn visit_application(&mut self, lhs: &Term<'src>, rhs: &Term<'src>) {
+    walk(self, lhs);
+    walk(self, rhs);
+  }
+}
+
+/// Drive a visitor over a term, dispatching on the kind of node
+pub fn walk<'src, V>(visitor: &mut V, term: &Term<'src>)
+where
+  V: Visitor<'src> + ?Sized,
+{
+  match term {
+    Term::Variable(name) => visitor.visit_variable(name),
+    Term::Abstraction { param, body } => visitor.visit_abstraction(param, body),
+    Term::Application { lhs, rhs } => visitor.visit_application(lhs, rhs),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  mod visitor {
+    use super::*;
+
+    use std::rc::Rc;
+
+    /// Counts the `Application` nodes in a term
+    #[derive(Default)]
+    struct ApplicationCounter(usize);
+
+    impl<'src> Visitor<'src> for ApplicationCounter {
+      fn visit_application(&mut self, lhs: &Term<'src>, rhs: &Term<'src>) {
+        self.0 += 1;
+        walk(self, lhs);
+        walk(self, rhs);
+      }
+    }
+
+    /// Collects variable occurrences in the order they're visited
+    #[derive(Default)]
+    struct VariableCollector(Vec<String>);
+
+    impl<'src> Visitor<'src> for VariableCollector {
+      fn visit_variable(&mut self, name: &str) {
+        self.0.push(name.to_string());
+      }
+    }
+
+    #[test]
+    fn walk_counts_applications() {
+      // (λp. λq. p q p) a b
+      let term = Term::Application {
+        lhs: Rc::new(Term::Application {
+          lhs: Rc::new(Term::Abstraction {
+            param: "p".into(),
+            body: Rc::new(Term::Abstraction {
+              param: "q".into(),
+              body: Rc::new(Term::Application {
+                lhs: Rc::new(Term::Application {
+                  lhs: Rc::new(Term::Variable("p".into())),
+                  rhs: Rc::new(Term::Variable("q".into())),
+                }),
+                rhs: Rc::new(Term::Variable("p".into())),
+              }),
+            }),
+          }),
+          rhs: Rc::new(Term::Variable("a".into())),
+        }),
+        rhs: Rc::new(Term::Variable("b".into())),
+      };
+      let mut counter = ApplicationCounter::default();
+      walk(&mut counter, &term);
+      assert_eq!(counter.0, 4);
+    }
+
+    #[test]
+    fn walk_visits_variables_left_to_right() {
+      // λx. x (y z)
+      let term = Term::Abstraction {
+        param: "x".into(),
+        body: Rc::new(Term::Application {
+          lhs: Rc::new(Term::Variable("x".into())),
+          rhs: Rc::new(Term::Application {
+            lhs: Rc::new(Term::Variable("y".into())),
+            rhs: Rc::new(Term::Variable("z".into())),
+          }),
+        }),
+      };
+      let mut collector = VariableCollector::default();
+      walk(&mut collector, &term);
+      assert_eq!(collector.0, vec!["x", "y", "z"]);
+    }
+  }
+}